@@ -22,18 +22,20 @@ use secret_toolkit::{
 
 use crate::contract_info::{ContractInfo, StoreContractInfo};
 use crate::msg::{
-    BattleDump, HandleAnswer, HandleMsg, InitMsg, PlayerDump, PlayerStats, QueryAnswer, QueryMsg,
-    TokenInfo, WaitingHero,
+    Affinity, BattleDump, HandleAnswer, HandleMsg, InitMsg, PlayerDump, PlayerStats, Progression,
+    QueryAnswer, QueryMsg, QueryWithPermit, TokenInfo, WaitingHero,
 };
+use crate::permit::{revoke_permit, validate_permit, Permission, Permit};
 use crate::rand::{extend_entropy, sha_256, Prng};
 use crate::state::{
     append_battle_for_addr, get_history, load, may_load, remove, save, Config, ExportConfig,
-    Leaderboards, Rank, StoreBattle, StoreHero, StorePlayerStats, StoreTokenInfo, StoreWaitingHero,
-    Tourney, TourneyStats, ADMIN_KEY, BOTS_KEY, CONFIG_KEY, EXPORT_CONFIG_KEY, IMPORT_FROM_KEY,
-    LEADERBOARDS_KEY, PREFIX_ALL_STATS, PREFIX_HISTORY, PREFIX_PLAYERS, PREFIX_SEEN,
-    PREFIX_TOURN_STATS, PREFIX_VIEW_KEY,
+    Leaderboards, Rank, StoreAffinities, StoreBattle, StoreHero, StorePlayerStats, StoreTokenInfo,
+    StoreWaitingHero, Tourney, TourneyStats, ADMIN_KEY, AFFINITIES_KEY, BOTS_KEY, CONFIG_KEY,
+    EXPORT_CONFIG_KEY, IMPORT_FROM_KEY, LEADERBOARDS_KEY, MY_ADDRESS_KEY, PREFIX_ALL_STATS,
+    PREFIX_HISTORY, PREFIX_PLAYERS, PREFIX_SEEN, PREFIX_TOURN_STATS, PREFIX_VIEW_KEY,
+    PROGRESSION_KEY,
 };
-use crate::stats::Stats;
+use crate::stats::{Stats, BASE_SKILL_CAP, CAP_PER_LEVEL, MAX_LEVEL};
 use crate::viewing_key::{ViewingKey, VIEWING_KEY_SIZE};
 
 pub const BLOCK_SIZE: usize = 256;
@@ -96,7 +98,34 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     };
     save(&mut deps.storage, CONFIG_KEY, &config)?;
     save(&mut deps.storage, ADMIN_KEY, &admin)?;
+    save(
+        &mut deps.storage,
+        MY_ADDRESS_KEY,
+        &deps.api.canonical_address(&env.contract.address)?,
+    )?;
     save(&mut deps.storage, LEADERBOARDS_KEY, &leaderboards)?;
+    // each element beats the next one in the cycle
+    let elements = ["fire", "earth", "water"];
+    let affinities = StoreAffinities {
+        bonus: 5,
+        affinities: elements
+            .iter()
+            .enumerate()
+            .map(|(i, e)| Affinity {
+                element: e.to_string(),
+                beats: vec![elements[(i + 1) % elements.len()].to_string()],
+            })
+            .collect(),
+    };
+    save(&mut deps.storage, AFFINITIES_KEY, &affinities)?;
+    let progression = Progression {
+        win_xp: 30,
+        tie_xp: 15,
+        loss_xp: 10,
+        thresholds: (1..=10).map(|l| 50 * l * (l + 1)).collect(),
+        skill_bonus: 1,
+    };
+    save(&mut deps.storage, PROGRESSION_KEY, &progression)?;
     let card_contract = config.card_versions.swap_remove(0);
     Ok(InitResponse {
         messages: vec![
@@ -151,6 +180,10 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::AddBots { bots } => try_add_bots(deps, env, bots),
         HandleMsg::RemoveBots { bots } => try_remove_bots(deps, env, bots),
         HandleMsg::ResetLeaderboard {} => try_reset_leaderboard(deps, env),
+        HandleMsg::SetAffinities { bonus, affinities } => {
+            try_set_affinities(deps, env, bonus, affinities)
+        }
+        HandleMsg::SetProgression { progression } => try_set_progression(deps, env, progression),
         HandleMsg::SetImportFromAddress { old_arena } => {
             try_set_import_from_addr(deps, env, old_arena)
         }
@@ -160,10 +193,36 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         } => try_import(deps, env, stats, battle_count),
         HandleMsg::Export {} => try_export(deps, env),
         HandleMsg::SetExportToContract { new_arena } => try_set_export_to(deps, env, new_arena),
+        HandleMsg::RevokePermit { permit_name, .. } => try_revoke_permit(deps, env, &permit_name),
     };
     pad_handle_result(response, BLOCK_SIZE)
 }
 
+/// Returns HandleResult
+///
+/// revoke a query permit
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `permit_name` - name of the permit to revoke
+pub fn try_revoke_permit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    permit_name: &str,
+) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    revoke_permit(&mut deps.storage, &sender_raw, permit_name)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RevokePermit {
+            permit_name: permit_name.to_string(),
+        })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// export the next block of player stats to the new arena
@@ -410,6 +469,119 @@ pub fn try_reset_leaderboard<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns HandleResult
+///
+/// set the elemental affinity table
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `bonus` - skill points gained or lost against each opponent with an elemental affinity
+/// * `affinities` - the elements each element beats
+pub fn try_set_affinities<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    bonus: u8,
+    affinities: Vec<Affinity>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    for (i, affinity) in affinities.iter().enumerate() {
+        if affinities
+            .iter()
+            .skip(i + 1)
+            .any(|a| a.element == affinity.element)
+        {
+            return Err(StdError::generic_err(format!(
+                "Element {} is listed more than once",
+                affinity.element
+            )));
+        }
+        if affinity.beats.contains(&affinity.element) {
+            return Err(StdError::generic_err(format!(
+                "Element {} can not beat itself",
+                affinity.element
+            )));
+        }
+    }
+    // an element can not both beat and be beaten by the same element
+    for affinity in affinities.iter() {
+        for beaten in affinity.beats.iter() {
+            if affinities
+                .iter()
+                .any(|a| a.element == *beaten && a.beats.contains(&affinity.element))
+            {
+                return Err(StdError::generic_err(format!(
+                    "Elements {} and {} can not beat each other",
+                    affinity.element, beaten
+                )));
+            }
+        }
+    }
+    let table = StoreAffinities { bonus, affinities };
+    save(&mut deps.storage, AFFINITIES_KEY, &table)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetAffinities {
+            bonus: table.bonus,
+            affinities: table.affinities,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the XP heroes earn in battles and the levels they can reach
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `progression` - XP earned for each battle result and the level thresholds
+pub fn try_set_progression<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    progression: Progression,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if progression.thresholds.len() > MAX_LEVEL as usize {
+        return Err(StdError::generic_err(format!(
+            "There can not be more than {} levels",
+            MAX_LEVEL
+        )));
+    }
+    let mut prev = 0u32;
+    for threshold in progression.thresholds.iter() {
+        if *threshold <= prev {
+            return Err(StdError::generic_err(
+                "Level thresholds must be positive and increasing",
+            ));
+        }
+        prev = *threshold;
+    }
+    save(&mut deps.storage, PROGRESSION_KEY, &progression)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetProgression { progression })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// add a list of addresses that auto-send fighters
@@ -875,6 +1047,7 @@ pub fn try_receive<S: Storage, A: Api, Q: Querier>(
                         version: pos as u8,
                     },
                     stats,
+                    description: priv_meta.description,
                 };
                 config.heroes.push(new_hero);
                 let bots: Vec<CanonicalAddr> =
@@ -908,6 +1081,8 @@ pub fn try_receive<S: Storage, A: Api, Q: Querier>(
                     let mut opt_winner = None;
                     let mut totals = vec![0i16; 4];
                     let mut ignore = vec![false; 3];
+                    let table: StoreAffinities = may_load(&deps.storage, AFFINITIES_KEY)?
+                        .unwrap_or_else(StoreAffinities::default);
                     for (i, hero) in config.heroes.iter().enumerate() {
                         let transfer = Transfer {
                             recipient: deps.api.human_address(&hero.owner)?,
@@ -930,7 +1105,7 @@ pub fn try_receive<S: Storage, A: Api, Q: Querier>(
                         ignore[i] = bots.contains(&hero.owner);
                         totals[i] = hero.stats.current.iter().map(|u| *u as i16).sum();
                         totals[3] += totals[i];
-                        let cur_score = hero.stats.current[fight_idx];
+                        let cur_score = effective_skill(&config.heroes, &table, i, fight_idx);
                         match cur_score.cmp(&win_score) {
                             Ordering::Greater => {
                                 win_score = cur_score;
@@ -1044,65 +1219,99 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
         QueryMsg::Bullpen {
             address,
             viewing_key,
-        } => query_bullpen(deps, &address, viewing_key),
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_bullpen(deps, &a)),
         QueryMsg::BattleHistory {
             address,
             viewing_key,
             page,
             page_size,
-        } => query_history(deps, &address, viewing_key, page, page_size),
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_history(deps, &a, page, page_size)),
         QueryMsg::Config {} => query_config(deps),
         QueryMsg::Bots {} => query_bots(deps),
+        QueryMsg::Affinities {} => query_affinities(deps),
+        QueryMsg::Progression {} => query_progression(deps),
         QueryMsg::Leaderboards {} => query_leaderboards(deps),
         QueryMsg::Tournament {} => query_tournament(deps),
         QueryMsg::PlayerStats {
             address,
             viewing_key,
-        } => query_player_stats(deps, address, viewing_key),
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_player_stats(deps, &a)),
         QueryMsg::Usage {} => query_usage(&deps.storage),
         QueryMsg::ExportStatus { admin, viewing_key } => {
-            query_export_status(deps, &admin, viewing_key)
+            check_viewer(deps, &admin, viewing_key).and_then(|a| query_export_status(deps, &a))
         }
         QueryMsg::DumpPlayerStats {
             admin,
             viewing_key,
             start_from,
             limit,
-        } => query_dump_stats(deps, &admin, viewing_key, start_from, limit),
+        } => check_viewer(deps, &admin, viewing_key)
+            .and_then(|a| query_dump_stats(deps, &a, start_from, limit)),
         QueryMsg::DumpBattleHistory {
             admin,
             viewing_key,
             start_from,
             limit,
-        } => query_dump_history(deps, &admin, viewing_key, start_from, limit),
+        } => check_viewer(deps, &admin, viewing_key)
+            .and_then(|a| query_dump_history(deps, &a, start_from, limit)),
+        QueryMsg::WithPermit { permit, query } => permit_queries(deps, &permit, query),
     };
     pad_query_result(response, BLOCK_SIZE)
 }
 
+/// Returns QueryResult from a query authenticated by a permit
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `permit` - a reference to the querier's Permit
+/// * `query` - the QueryWithPermit to perform
+fn permit_queries<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    permit: &Permit,
+    query: QueryWithPermit,
+) -> QueryResult {
+    let my_address_raw: CanonicalAddr = load(&deps.storage, MY_ADDRESS_KEY)?;
+    let my_address = deps.api.human_address(&my_address_raw)?;
+    let permission = match query {
+        QueryWithPermit::Bullpen {} => Permission::Bullpen,
+        QueryWithPermit::BattleHistory { .. } => Permission::BattleHistory,
+        QueryWithPermit::PlayerStats {} => Permission::PlayerStats,
+        _ => Permission::Owner,
+    };
+    let address_raw = validate_permit(&deps.storage, permit, &my_address, permission)?;
+    match query {
+        QueryWithPermit::Bullpen {} => query_bullpen(deps, &address_raw),
+        QueryWithPermit::BattleHistory { page, page_size } => {
+            query_history(deps, &address_raw, page, page_size)
+        }
+        QueryWithPermit::PlayerStats {} => query_player_stats(deps, &address_raw),
+        QueryWithPermit::ExportStatus {} => query_export_status(deps, &address_raw),
+        QueryWithPermit::DumpPlayerStats { start_from, limit } => {
+            query_dump_stats(deps, &address_raw, start_from, limit)
+        }
+        QueryWithPermit::DumpBattleHistory { start_from, limit } => {
+            query_dump_history(deps, &address_raw, start_from, limit)
+        }
+    }
+}
+
 /// Returns QueryResult dumping all battle histories
 ///
 /// # Arguments
 ///
 /// * `deps` - a reference to Extern containing all the contract's external dependencies
-/// * `admin` - a reference to the admin's address
-/// * `viewing_key` - admin's viewing key
+/// * `admin` - a reference to the authenticated querier's address
 /// * `start_from` - Optional battle index to start display from
 /// * `limit` - Optional number of battles to display
 pub fn query_dump_history<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    admin: &HumanAddr,
-    viewing_key: String,
+    admin: &CanonicalAddr,
     start_from: Option<u64>,
     limit: Option<u64>,
 ) -> QueryResult {
-    let real_admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
-    let input_raw = deps.api.canonical_address(admin)?;
-    if real_admin != input_raw {
-        return Err(StdError::generic_err(
-            "This is an admin query. Admin queries can only be run from admin address",
-        ));
-    }
-    check_key(&deps.storage, &input_raw, viewing_key)?;
+    check_admin(&deps.storage, admin)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
     let versions = config
         .card_versions
@@ -1131,25 +1340,16 @@ pub fn query_dump_history<S: Storage, A: Api, Q: Querier>(
 /// # Arguments
 ///
 /// * `deps` - a reference to Extern containing all the contract's external dependencies
-/// * `admin` - a reference to the admin's address
-/// * `viewing_key` - admin's viewing key
+/// * `admin` - a reference to the authenticated querier's address
 /// * `start_from` - Optional player index to start display from
 /// * `limit` - Optional number of players' stats to display
 pub fn query_dump_stats<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    admin: &HumanAddr,
-    viewing_key: String,
+    admin: &CanonicalAddr,
     start_from: Option<u32>,
     limit: Option<u32>,
 ) -> QueryResult {
-    let real_admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
-    let input_raw = deps.api.canonical_address(admin)?;
-    if real_admin != input_raw {
-        return Err(StdError::generic_err(
-            "This is an admin query. Admin queries can only be run from admin address",
-        ));
-    }
-    check_key(&deps.storage, &input_raw, viewing_key)?;
+    check_admin(&deps.storage, admin)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
     let start = start_from.unwrap_or(0);
     let count = limit.unwrap_or(256);
@@ -1188,21 +1388,12 @@ pub fn query_dump_stats<S: Storage, A: Api, Q: Querier>(
 /// # Arguments
 ///
 /// * `deps` - a reference to Extern containing all the contract's external dependencies
-/// * `admin` - a reference to the admin's address
-/// * `viewing_key` - admin's viewing key
+/// * `admin` - a reference to the authenticated querier's address
 pub fn query_export_status<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    admin: &HumanAddr,
-    viewing_key: String,
+    admin: &CanonicalAddr,
 ) -> QueryResult {
-    let real_admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
-    let input_raw = deps.api.canonical_address(admin)?;
-    if real_admin != input_raw {
-        return Err(StdError::generic_err(
-            "This is an admin query. Admin queries can only be run from admin address",
-        ));
-    }
-    check_key(&deps.storage, &input_raw, viewing_key)?;
+    check_admin(&deps.storage, admin)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
     let may_export_conf: Option<ExportConfig> = may_load(&deps.storage, EXPORT_CONFIG_KEY)?;
     let mut next_block: Option<u32> = None;
@@ -1248,20 +1439,48 @@ pub fn query_bots<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> Que
     })
 }
 
+/// Returns QueryResult displaying the elemental affinity table
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+pub fn query_affinities<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+    let table: StoreAffinities =
+        may_load(&deps.storage, AFFINITIES_KEY)?.unwrap_or_else(StoreAffinities::default);
+
+    to_binary(&QueryAnswer::Affinities {
+        bonus: table.bonus,
+        affinities: table.affinities,
+    })
+}
+
+/// Returns QueryResult displaying the XP earned in battles and the levels heroes can reach
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+pub fn query_progression<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> QueryResult {
+    let progression: Progression =
+        may_load(&deps.storage, PROGRESSION_KEY)?.unwrap_or_else(Progression::default);
+
+    to_binary(&QueryAnswer::Progression {
+        progression,
+        base_skill_cap: BASE_SKILL_CAP,
+        cap_per_level: CAP_PER_LEVEL,
+    })
+}
+
 /// Returns QueryResult displaying a player's tournament stats and all-time stats
 ///
 /// # Arguments
 ///
 /// * `deps` - a reference to Extern containing all the contract's external dependencies
-/// * `address` - querier's address
-/// * `viewing_key` - querier's viewing key
+/// * `address_raw` - a reference to the authenticated querier's address
 pub fn query_player_stats<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    address: HumanAddr,
-    viewing_key: String,
+    address_raw: &CanonicalAddr,
 ) -> QueryResult {
-    let address_raw = deps.api.canonical_address(&address)?;
-    check_key(&deps.storage, &address_raw, viewing_key)?;
+    let address = deps.api.human_address(address_raw)?;
     let address_slice = address_raw.as_slice();
     let leaderboards: Leaderboards = load(&deps.storage, LEADERBOARDS_KEY)?;
     let trn_store = ReadonlyPrefixedStorage::new(PREFIX_TOURN_STATS, &deps.storage);
@@ -1379,17 +1598,14 @@ pub fn query_leaderboards<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>
 
 pub fn query_history<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    address: &HumanAddr,
-    viewing_key: String,
+    address_raw: &CanonicalAddr,
     page: Option<u32>,
     page_size: Option<u32>,
 ) -> QueryResult {
-    let address_raw = deps.api.canonical_address(address)?;
-    check_key(&deps.storage, &address_raw, viewing_key)?;
     let history = get_history(
         &deps.api,
         &deps.storage,
-        &address_raw,
+        address_raw,
         page.unwrap_or(0),
         page_size.unwrap_or(30),
     )?;
@@ -1402,18 +1618,15 @@ pub fn query_history<S: Storage, A: Api, Q: Querier>(
 /// # Arguments
 ///
 /// * `deps` - a reference to Extern containing all the contract's external dependencies
-/// * `address` - querier's address
-/// * `viewing_key` - querier's viewing key
+/// * `address_raw` - a reference to the authenticated querier's address
 pub fn query_bullpen<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    address: &HumanAddr,
-    viewing_key: String,
+    address_raw: &CanonicalAddr,
 ) -> QueryResult {
-    let address_raw = deps.api.canonical_address(address)?;
-    check_key(&deps.storage, &address_raw, viewing_key)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
     let heroes_waiting = config.heroes.len() as u8;
-    let your_hero = if let Some(hero) = config.heroes.into_iter().find(|h| h.owner == address_raw) {
+    let your_hero = if let Some(hero) = config.heroes.into_iter().find(|h| h.owner == *address_raw)
+    {
         Some(WaitingHero {
             name: hero.name,
             token_info: TokenInfo {
@@ -1434,6 +1647,39 @@ pub fn query_bullpen<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns StdResult<CanonicalAddr> of the querier after validating their viewing key
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the querier's address
+/// * `viewing_key` - String key used for authentication
+fn check_viewer<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    viewing_key: String,
+) -> StdResult<CanonicalAddr> {
+    let address_raw = deps.api.canonical_address(address)?;
+    check_key(&deps.storage, &address_raw, viewing_key)?;
+    Ok(address_raw)
+}
+
+/// Returns StdResult<()> result of verifying the querier is the admin
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `address` - a reference to the authenticated querier's address
+fn check_admin<S: ReadonlyStorage>(storage: &S, address: &CanonicalAddr) -> StdResult<()> {
+    let real_admin: CanonicalAddr = load(storage, ADMIN_KEY)?;
+    if real_admin != *address {
+        return Err(StdError::generic_err(
+            "This is an admin query. Admin queries can only be run from admin address",
+        ));
+    }
+    Ok(())
+}
+
 /// Returns StdResult<()> result of validating an address' viewing key
 ///
 /// # Arguments
@@ -1474,6 +1720,63 @@ fn get_prng(env: &Env, seed: &[u8], entropy: &[u8]) -> Prng {
     Prng::new(seed, &rng_entropy)
 }
 
+/// Returns u8 of a fighter's skill value after applying its elemental affinities against the
+/// other fighters
+///
+/// # Arguments
+///
+/// * `heroes` - the heroes in the battle
+/// * `table` - a reference to the elemental affinity table
+/// * `idx` - index of the fighter
+/// * `skill` - index of the skill used in the battle
+fn effective_skill(
+    heroes: &[StoreWaitingHero],
+    table: &StoreAffinities,
+    idx: usize,
+    skill: usize,
+) -> u8 {
+    let value = heroes[idx].stats.current[skill];
+    let mine = match heroes[idx].stats.element.as_ref() {
+        Some(e) => e,
+        None => return value,
+    };
+    let beats = |a: &String, b: &String| {
+        table
+            .affinities
+            .iter()
+            .any(|af| af.element == *a && af.beats.contains(b))
+    };
+    let mut modifier = 0i16;
+    for (i, foe) in heroes.iter().enumerate() {
+        if i == idx {
+            continue;
+        }
+        if let Some(theirs) = foe.stats.element.as_ref() {
+            if beats(mine, theirs) {
+                modifier += table.bonus as i16;
+            } else if beats(theirs, mine) {
+                modifier -= table.bonus as i16;
+            }
+        }
+    }
+    (value as i16 + modifier).clamp(0, 255) as u8
+}
+
+/// Returns u32 of the XP a fighter earns in a battle.  The XP for the battle result is scaled
+/// by the opponents' average skill total relative to the fighter's own, and is kept between
+/// half and double the result's XP
+///
+/// # Arguments
+///
+/// * `result_xp` - XP for the battle result
+/// * `own_total` - fighter's skill total
+/// * `opponents_total` - combined skill total of the fighter's opponents
+fn battle_xp(result_xp: u32, own_total: i16, opponents_total: i16) -> u32 {
+    let result_xp = result_xp as u64;
+    let scaled = result_xp * opponents_total.max(0) as u64 / (2 * own_total.max(1) as u64);
+    scaled.clamp(result_xp / 2, 2 * result_xp) as u32
+}
+
 // list of transfers for each card version in the fight
 pub struct VersionTransfer {
     pub version: u8,
@@ -1500,15 +1803,23 @@ fn update_skills<S: Storage>(
     let is_tie = winners.len() != 1;
     let mut leaderboards: Leaderboards = load(storage, LEADERBOARDS_KEY)?;
     let mut save_boards = false;
+    let progression: Progression =
+        may_load(storage, PROGRESSION_KEY)?.unwrap_or_else(Progression::default);
+    let all_totals: i16 = totals.iter().take(3).sum();
     for (i, hero) in fighters.into_iter().enumerate() {
+        let cap = hero.stats.skill_cap();
         let pre_battle_skills = hero.stats.current;
         let base = hero.stats.base;
+        let element = hero.stats.element;
+        let pre_battle_xp = hero.stats.xp;
+        let pre_battle_level = hero.stats.level;
         let post_battle_skills: Vec<u8>;
         let mut wins = 0u8;
         let mut ties = 0u8;
         let mut lose_ties = 0u8;
         let mut losses = 0u8;
         let delta: i8;
+        let result_xp: u32;
         if is_tie {
             // no skill changes on ties
             post_battle_skills = pre_battle_skills.clone();
@@ -1516,16 +1827,19 @@ fn update_skills<S: Storage>(
             if winners.contains(&i) {
                 ties = 1;
                 delta = 1;
+                result_xp = progression.tie_xp;
             // loser gets 0
             } else {
                 lose_ties = 1;
                 delta = 0;
+                result_xp = progression.loss_xp;
             }
         // if this is the winner, give him an upgrade
         } else if i == winners[0] {
             // winners get 3 points
             wins = 1;
             delta = 3;
+            result_xp = progression.win_xp;
             let power_diff = 2 * totals[i] - totals[3];
             let mut rand_iter = rand.iter();
             let base_upgrade: i8 = if power_diff > 160 {
@@ -1550,8 +1864,8 @@ fn update_skills<S: Storage>(
                     let modified = base_upgrade + adjust[(*r as usize) % mod_val];
                     if modified > 0 {
                         let new_skill = modified as u8 + u;
-                        if new_skill > 100 {
-                            Ok(100)
+                        if new_skill > cap {
+                            Ok(cap)
                         } else {
                             Ok(new_skill)
                         }
@@ -1565,6 +1879,7 @@ fn update_skills<S: Storage>(
             // losers lose a point
             losses = 1;
             delta = -1;
+            result_xp = progression.loss_xp;
             let mut base_iter = base.iter();
             post_battle_skills = pre_battle_skills
                 .iter()
@@ -1621,17 +1936,31 @@ fn update_skills<S: Storage>(
             );
             save_boards = true;
         }
-        if pre_battle_skills != post_battle_skills {
-            let stats = Stats {
-                base,
-                current: post_battle_skills.clone(),
-            };
+        let xp =
+            pre_battle_xp.saturating_add(battle_xp(result_xp, totals[i], all_totals - totals[i]));
+        let mut stats = Stats {
+            base,
+            current: post_battle_skills,
+            element,
+            xp,
+            level: pre_battle_level.max(progression.level_for_xp(xp)),
+        };
+        // each new level raises the skill cap and adds the bonus to every skill
+        if stats.level > pre_battle_level {
+            let new_cap = stats.skill_cap() as u16;
+            let bonus = (stats.level - pre_battle_level) as u16 * progression.skill_bonus as u16;
+            for skill in stats.base.iter_mut().chain(stats.current.iter_mut()) {
+                *skill = (*skill as u16 + bonus).min(new_cap) as u8;
+            }
+        }
+        let post_battle_skills = stats.current.clone();
+        if pre_battle_skills != post_battle_skills || pre_battle_xp != xp {
             let stats_str = serde_json::to_string(&stats).map_err(|e| {
                 StdError::generic_err(format!("Error serializing card stats: {}", e))
             })?;
             let metadata = Metadata {
                 name: Some(hero.name.clone()),
-                description: None,
+                description: hero.description,
                 image: Some(stats_str),
             };
             messages.push(set_private_metadata_msg(
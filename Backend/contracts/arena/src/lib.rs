@@ -2,6 +2,7 @@
 pub mod contract;
 mod contract_info;
 pub mod msg;
+pub mod permit;
 mod rand;
 pub mod state;
 pub mod stats;
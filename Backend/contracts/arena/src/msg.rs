@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_std::{Binary, HumanAddr};
 
 use crate::contract_info::ContractInfo;
+use crate::permit::Permit;
 use crate::stats::Stats;
 
 /// Instantiation message
@@ -89,6 +90,26 @@ pub enum HandleMsg {
     },
     /// reset the tournament leaderboard
     ResetLeaderboard {},
+    /// set the elemental affinity table
+    SetAffinities {
+        /// skill points a hero gains against each opponent its element beats, and loses
+        /// against each opponent whose element beats it
+        bonus: u8,
+        /// the elements each element beats
+        affinities: Vec<Affinity>,
+    },
+    /// set the XP heroes earn in battles and the levels they can reach
+    SetProgression {
+        /// XP earned for each battle result and the level thresholds
+        progression: Progression,
+    },
+    /// revoke a query permit so it can no longer be used
+    RevokePermit {
+        /// name of the permit to revoke
+        permit_name: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
 }
 
 /// Responses from handle functions
@@ -97,6 +118,8 @@ pub enum HandleMsg {
 pub enum HandleAnswer {
     /// response from setting or creating a viewing key
     ViewingKey { key: String },
+    /// response from revoking a permit
+    RevokePermit { permit_name: String },
     /// response from withdrawing a hero from the bullpen
     ChickenOut { message: String },
     /// response from changing the admin address
@@ -111,6 +134,13 @@ pub enum HandleAnswer {
     RemoveBots { removed_bots: Vec<HumanAddr> },
     /// response from resetting the tournament leaderboard
     ResetLeaderboard { timestamp: u64 },
+    /// response from setting the elemental affinity table
+    SetAffinities {
+        bonus: u8,
+        affinities: Vec<Affinity>,
+    },
+    /// response from setting the XP and level progression
+    SetProgression { progression: Progression },
     /// response from setting an old arena contract allowed to export player stats
     SetImportFromAddress { old_arena: HumanAddr },
     /// response from importing player stats
@@ -157,6 +187,10 @@ pub enum QueryMsg {
     Usage {},
     /// display list of auto-send addresses
     Bots {},
+    /// display the elemental affinity table
+    Affinities {},
+    /// display the XP heroes earn in battles and the levels they can reach
+    Progression {},
     /// display the leaderboards
     Leaderboards {},
     /// display tournament info
@@ -190,6 +224,48 @@ pub enum QueryMsg {
         /// optional number of battles to display
         limit: Option<u64>,
     },
+    /// perform a query authenticated by a permit instead of a viewing key
+    WithPermit {
+        /// permit signed by the querier
+        permit: Permit,
+        /// the query to perform
+        query: QueryWithPermit,
+    },
+}
+
+/// queries that can be authenticated by a permit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryWithPermit {
+    /// display the number of heroes waiting to battle, and the stats of the querier's hero
+    /// if applicable.  Requires the bullpen permission
+    Bullpen {},
+    /// display the querier's battle history.  Requires the battle_history permission
+    BattleHistory {
+        /// optional page to display
+        page: Option<u32>,
+        /// optional number of battles to display
+        page_size: Option<u32>,
+    },
+    /// display the querier's stats.  Requires the player_stats permission
+    PlayerStats {},
+    /// display player stats export status.  Requires the owner permission signed by the admin
+    ExportStatus {},
+    /// admin dump of all players' all-time stats.  Requires the owner permission signed by
+    /// the admin
+    DumpPlayerStats {
+        /// optional index of player to start display.  Use this for pagination
+        start_from: Option<u32>,
+        /// optional number of players' stats to display
+        limit: Option<u32>,
+    },
+    /// admin dump history of all battles.  Requires the owner permission signed by the admin
+    DumpBattleHistory {
+        /// optional index of battle to start display.  Use this for pagination
+        start_from: Option<u64>,
+        /// optional number of battles to display
+        limit: Option<u64>,
+    },
 }
 
 /// responses from queries
@@ -212,6 +288,23 @@ pub enum QueryAnswer {
     Bots {
         bots: Vec<HumanAddr>,
     },
+    /// elemental affinity table
+    Affinities {
+        /// skill points a hero gains against each opponent its element beats, and loses
+        /// against each opponent whose element beats it
+        bonus: u8,
+        /// the elements each element beats
+        affinities: Vec<Affinity>,
+    },
+    /// XP and level progression
+    Progression {
+        /// XP earned for each battle result and the level thresholds
+        progression: Progression,
+        /// highest value a skill can have before any levels are gained
+        base_skill_cap: u8,
+        /// amount the skill cap rises with each level
+        cap_per_level: u8,
+    },
     /// point leaderboards
     Leaderboards {
         /// seconds after 01/01/1970 in which the tournament started
@@ -260,6 +353,41 @@ pub enum QueryAnswer {
     },
 }
 
+/// elements that an element has the advantage over
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Affinity {
+    /// the element
+    pub element: String,
+    /// the elements it beats
+    pub beats: Vec<String>,
+}
+
+/// XP earned in battles and the levels heroes can reach
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+pub struct Progression {
+    /// XP for winning a battle
+    pub win_xp: u32,
+    /// XP for tying for first place
+    pub tie_xp: u32,
+    /// XP for losing a battle
+    pub loss_xp: u32,
+    /// XP needed to reach each level.  The first threshold is for level 1
+    pub thresholds: Vec<u32>,
+    /// skill points added to every skill for each level gained
+    pub skill_bonus: u8,
+}
+
+impl Progression {
+    /// Returns u8 of the level reached with the given XP
+    ///
+    /// # Arguments
+    ///
+    /// * `xp` - the hero's XP
+    pub fn level_for_xp(&self, xp: u32) -> u8 {
+        self.thresholds.iter().take_while(|t| xp >= **t).count() as u8
+    }
+}
+
 /// info of hero waiting to fight
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct WaitingHero {
@@ -284,7 +412,7 @@ pub struct Battle {
     pub skill_used: u8,
     /// winning hero's name, if any
     pub winner: Option<String>,
-    /// the skill value that won the battle
+    /// the skill value, after elemental affinities, that won the battle
     pub winning_skill_value: u8,
     /// true if the querier's hero won the battle
     pub i_won: bool,
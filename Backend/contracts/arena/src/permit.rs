@@ -0,0 +1,267 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Binary, CanonicalAddr, HumanAddr, ReadonlyStorage, StdError, StdResult, Storage,
+};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+
+use ripemd160::{Digest, Ripemd160};
+use secp256k1::{verify, Message, PublicKey, Signature};
+
+use crate::rand::sha_256;
+
+/// prefix for storage of revoked permits
+pub const PREFIX_REVOKED_PERMITS: &[u8] = b"revokedperms";
+
+/// a query permit signed offline by the querier
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Permit {
+    pub params: PermitParams,
+    pub signature: PermitSignature,
+}
+
+/// the signed contents of a permit
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PermitParams {
+    /// contracts this permit may be used with
+    pub allowed_tokens: Vec<HumanAddr>,
+    /// name used to revoke the permit
+    pub permit_name: String,
+    /// chain the permit was signed for
+    pub chain_id: String,
+    /// queries this permit may be used for
+    pub permissions: Vec<Permission>,
+}
+
+/// the signature of a permit
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PermitSignature {
+    pub pub_key: PubKey,
+    pub signature: Binary,
+}
+
+/// the signer's public key
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct PubKey {
+    /// ignored, but must be "tendermint/PubKeySecp256k1" for wallets to accept it
+    pub r#type: String,
+    /// compressed secp256k1 public key
+    pub value: Binary,
+}
+
+/// queries a permit can grant
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// the querier's hero waiting in the bullpen
+    Bullpen,
+    /// the querier's battle history
+    BattleHistory,
+    /// the querier's player stats
+    PlayerStats,
+    /// every query, including admin queries if signed by the admin
+    Owner,
+}
+
+/// the amino sign doc wallets sign for a permit.  Fields must stay in alphabetical order
+#[derive(Serialize)]
+struct SignDoc<'a> {
+    account_number: &'a str,
+    chain_id: &'a str,
+    fee: Fee<'a>,
+    memo: &'a str,
+    msgs: Vec<PermitMsg<'a>>,
+    sequence: &'a str,
+}
+
+#[derive(Serialize)]
+struct Fee<'a> {
+    amount: Vec<FeeCoin<'a>>,
+    gas: &'a str,
+}
+
+#[derive(Serialize)]
+struct FeeCoin<'a> {
+    amount: &'a str,
+    denom: &'a str,
+}
+
+#[derive(Serialize)]
+struct PermitMsg<'a> {
+    r#type: &'a str,
+    value: PermitValue<'a>,
+}
+
+#[derive(Serialize)]
+struct PermitValue<'a> {
+    allowed_tokens: &'a [HumanAddr],
+    permissions: &'a [Permission],
+    permit_name: &'a str,
+}
+
+/// Returns StdResult<CanonicalAddr> of the permit's signer after verifying the permit
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `permit` - a reference to the permit
+/// * `my_address` - a reference to this contract's address
+/// * `permission` - the Permission the query needs
+pub fn validate_permit<S: ReadonlyStorage>(
+    storage: &S,
+    permit: &Permit,
+    my_address: &HumanAddr,
+    permission: Permission,
+) -> StdResult<CanonicalAddr> {
+    let params = &permit.params;
+    if !params.allowed_tokens.contains(my_address) {
+        return Err(StdError::generic_err(format!(
+            "This permit is not valid for contract {}",
+            my_address
+        )));
+    }
+    if !params.permissions.contains(&permission) && !params.permissions.contains(&Permission::Owner)
+    {
+        return Err(StdError::generic_err(
+            "This permit does not grant permission for this query",
+        ));
+    }
+    // the signer's address is the ripemd160 of the sha256 of their public key
+    let pub_key = permit.signature.pub_key.value.as_slice();
+    let signer = CanonicalAddr(Binary(
+        Ripemd160::digest(&sha_256(pub_key)).as_slice().to_vec(),
+    ));
+    let rvk_store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_REVOKED_PERMITS, signer.as_slice()], storage);
+    if rvk_store.get(params.permit_name.as_bytes()).is_some() {
+        return Err(StdError::generic_err(format!(
+            "Permit {} was revoked",
+            params.permit_name
+        )));
+    }
+    let sign_doc = SignDoc {
+        account_number: "0",
+        chain_id: &params.chain_id,
+        fee: Fee {
+            amount: vec![FeeCoin {
+                amount: "0",
+                denom: "uscrt",
+            }],
+            gas: "1",
+        },
+        memo: "",
+        msgs: vec![PermitMsg {
+            r#type: "query_permit",
+            value: PermitValue {
+                allowed_tokens: &params.allowed_tokens,
+                permissions: &params.permissions,
+                permit_name: &params.permit_name,
+            },
+        }],
+        sequence: "0",
+    };
+    let message = Message::parse(&sha_256(to_binary(&sign_doc)?.as_slice()));
+    let signature = Signature::parse_slice(permit.signature.signature.as_slice())
+        .map_err(|_| StdError::generic_err("Invalid permit signature format"))?;
+    let public_key = PublicKey::parse_slice(pub_key, None)
+        .map_err(|_| StdError::generic_err("Invalid permit public key format"))?;
+    if !verify(&message, &signature, &public_key) {
+        return Err(StdError::generic_err(
+            "Failed to verify the permit signature",
+        ));
+    }
+    Ok(signer)
+}
+
+/// Returns StdResult<()> after revoking a permit
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `address` - a reference to the address revoking the permit
+/// * `permit_name` - name of the permit to revoke
+pub fn revoke_permit<S: Storage>(
+    storage: &mut S,
+    address: &CanonicalAddr,
+    permit_name: &str,
+) -> StdResult<()> {
+    let mut rvk_store =
+        PrefixedStorage::multilevel(&[PREFIX_REVOKED_PERMITS, address.as_slice()], storage);
+    rvk_store.set(permit_name.as_bytes(), &[1]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    // signed offline with a fixed test key for chain secret-4
+    const PUB_KEY: &str = "ArtQ4tiaTtcGY9CAZZ/grUubw+BsF6InQzlmy1nO7gIN";
+    const SIGNATURE: &str = "vtljjcQf/gwP3miONGwvVU34SE8kzzPVhw2ZK0+IdUF8vEfiYT1spCgU0PIAdwFc\
+         WpEZvd1LAxvpgX4Q0w+Rjw==";
+    const SIGNER: &str = "o5C7TW1KtXB2fvIfZsPtwaTWkCY=";
+
+    fn signed_permit() -> Permit {
+        Permit {
+            params: PermitParams {
+                allowed_tokens: vec![HumanAddr("secret1arena".to_string())],
+                permit_name: "test".to_string(),
+                chain_id: "secret-4".to_string(),
+                permissions: vec![Permission::BattleHistory],
+            },
+            signature: PermitSignature {
+                pub_key: PubKey {
+                    r#type: "tendermint/PubKeySecp256k1".to_string(),
+                    value: Binary::from_base64(PUB_KEY).unwrap(),
+                },
+                signature: Binary::from_base64(SIGNATURE).unwrap(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_validate_permit() {
+        let storage = MockStorage::new();
+        let my_address = HumanAddr("secret1arena".to_string());
+        let signer = validate_permit(
+            &storage,
+            &signed_permit(),
+            &my_address,
+            Permission::BattleHistory,
+        )
+        .unwrap();
+        assert_eq!(signer, CanonicalAddr(Binary::from_base64(SIGNER).unwrap()));
+    }
+
+    #[test]
+    fn test_validate_permit_tampered() {
+        let storage = MockStorage::new();
+        let my_address = HumanAddr("secret1arena".to_string());
+        let mut permit = signed_permit();
+        permit.params.permissions.push(Permission::Owner);
+        let err =
+            validate_permit(&storage, &permit, &my_address, Permission::BattleHistory).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(msg, "Failed to verify the permit signature")
+            }
+            _ => panic!("Unexpected error: {:?}", err),
+        }
+
+        let mut permit = signed_permit();
+        permit
+            .params
+            .allowed_tokens
+            .push(HumanAddr("secret1other".to_string()));
+        let err =
+            validate_permit(&storage, &permit, &my_address, Permission::BattleHistory).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(msg, "Failed to verify the permit signature")
+            }
+            _ => panic!("Unexpected error: {:?}", err),
+        }
+    }
+}
@@ -12,7 +12,7 @@ use secret_toolkit::{
 };
 
 use crate::contract_info::{ContractInfo, StoreContractInfo};
-use crate::msg::{Battle, BattleDump, Hero, HeroDump, PlayerStats, TokenInfo};
+use crate::msg::{Affinity, Battle, BattleDump, Hero, HeroDump, PlayerStats, TokenInfo};
 use crate::stats::Stats;
 
 pub const CONFIG_KEY: &[u8] = b"config";
@@ -28,6 +28,9 @@ pub const BOTS_KEY: &[u8] = b"bots";
 pub const LEADERBOARDS_KEY: &[u8] = b"ldrbds";
 pub const IMPORT_FROM_KEY: &[u8] = b"import";
 pub const EXPORT_CONFIG_KEY: &[u8] = b"export";
+pub const MY_ADDRESS_KEY: &[u8] = b"myaddr";
+pub const AFFINITIES_KEY: &[u8] = b"affinity";
+pub const PROGRESSION_KEY: &[u8] = b"progress";
 
 /// arena config
 #[derive(Serialize, Deserialize)]
@@ -166,6 +169,18 @@ pub struct StoreWaitingHero {
     pub token_info: StoreTokenInfo,
     /// hero's stats
     pub stats: Stats,
+    /// description in the hero's private metadata
+    pub description: Option<String>,
+}
+
+/// elemental affinity table
+#[derive(Serialize, Deserialize, Default)]
+pub struct StoreAffinities {
+    /// skill points a hero gains against each opponent its element beats, and loses against
+    /// each opponent whose element beats it
+    pub bonus: u8,
+    /// the elements each element beats
+    pub affinities: Vec<Affinity>,
 }
 
 /// hero info
@@ -247,7 +262,7 @@ pub struct StoreBattle {
     pub skill_used: u8,
     /// index of winning hero
     pub winner: Option<u8>,
-    /// winning skill value
+    /// winning skill value after elemental affinities
     pub winning_skill_value: u8,
 }
 
@@ -1,30 +1,51 @@
 use cosmwasm_std::{
-    to_binary, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Env, Extern, HandleResponse,
-    HandleResult, HumanAddr, InitResponse, InitResult, Querier, QueryResult, ReadonlyStorage,
-    StdError, StdResult, Storage, Uint128,
+    from_binary, to_binary, Api, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Env, Extern,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, Querier, QueryResult,
+    ReadonlyStorage, StdError, StdResult, Storage, Uint128,
 };
 
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+
 use secret_toolkit::{
+    snip20::{register_receive_msg, transfer_msg},
     snip721::{
-        batch_burn_nft_msg, batch_mint_nft_msg, set_private_metadata_msg, set_viewing_key_msg,
-        Burn, Metadata, Mint, ViewerInfo,
+        batch_burn_nft_msg, batch_mint_nft_msg, set_private_metadata_msg, set_public_metadata_msg,
+        set_viewing_key_msg, Burn, Metadata, Mint, ViewerInfo,
     },
-    utils::{pad_handle_result, pad_query_result, Query},
+    utils::{pad_handle_result, pad_query_result, HandleCallback, Query},
 };
 
 use crate::contract_info::{ContractInfo, StoreContractInfo};
 use crate::msg::{
-    HandleAnswer, HandleMsg, HeroInfo, InitMsg, QueryAnswer, QueryMsg, ResponseStatus::Success,
+    Aggregation, AirdropEntry, HandleAnswer, HandleMsg, HeroInfo, InitMsg, Lineage, LineageNode,
+    MintRecord, NameRules, PackTier, PackTierInfo, PhaseAccess, PhaseAllowance, PowerBracket,
+    PromoCode, PromoCodeInfo, QueryAnswer, QueryMsg, QueryWithPermit, ReceiveMsg, ReferralReward,
+    ResponseStatus::Success, RevenueShare, SalePhase, SkillOutcome, SkillPreview, TokenPrice,
+    UpgradeRecipe, UpgradeRecord,
 };
+use crate::multisig::MultiSigHandleMsg;
+use crate::permit::{revoke_permit, validate_permit, Permission, Permit};
 use crate::rand::{extend_entropy, sha_256, Prng};
 use crate::snip721::{NftDossierResponse, Snip721QueryMsg};
-use crate::state::{load, save, Config, ADMIN_KEY, CONFIG_KEY, VKEY_KEY};
+use crate::state::{
+    append_record, get_records, json_load, json_may_load, json_save, load, may_load,
+    may_load_lineage, may_load_migration, save, save_lineage, save_migration, Config,
+    StoreRevenueShare, StoreTokenPrice, StoredLineage, StoredMintRecord, StoredPackTier,
+    StoredPayment, StoredPromoCode, StoredReferrer, StoredReward, StoredUpgradeRecord, ADMIN_KEY,
+    BANNED_KEY, CONFIG_KEY, MY_ADDRESS_KEY, NAME_RULES_KEY, PHASES_KEY, PREFIX_ALLOWLIST,
+    PREFIX_MINT_COUNTS, PREFIX_MINT_HISTORY, PREFIX_MINT_IDS, PREFIX_NAMES, PREFIX_PHASE_MINTS,
+    PREFIX_PROMO_CODES, PREFIX_PROMO_IDS, PREFIX_PROMO_REDEEMERS, PREFIX_REFERRAL_CODES,
+    PREFIX_REFERRERS, PREFIX_UPGRADE_HISTORY, PREFIX_UPGRADE_IDS, PREFIX_VIEW_KEY, RECIPES_KEY,
+    TIERS_KEY, TOTAL_SHARES, VKEY_KEY,
+};
 use crate::stats::Stats;
-use crate::viewing_key::ViewingKey;
+use crate::viewing_key::{ViewingKey, VIEWING_KEY_SIZE};
 
 use serde_json_wasm as serde_json;
 
 pub const BLOCK_SIZE: usize = 256;
+/// most points a fused hero's skill can differ from the average of its parents' base skills
+pub const FUSION_MUTATION: u8 = 5;
 
 ////////////////////////////////////// Init ///////////////////////////////////////
 /// Returns InitResult
@@ -47,15 +68,98 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     save(&mut deps.storage, VKEY_KEY, &keystr)?;
     let admin = deps.api.canonical_address(&env.message.sender)?;
     save(&mut deps.storage, ADMIN_KEY, &admin)?;
+    let my_address = deps.api.canonical_address(&env.contract.address)?;
+    save(&mut deps.storage, MY_ADDRESS_KEY, &my_address)?;
+    let multi_sig = deps.api.canonical_address(&msg.multi_sig)?;
     let config = Config {
         card_versions: vec![msg.card_contract.get_store(&deps.api)?],
         minting_halt: false,
         upgrade_halt: false,
-        multi_sig: deps.api.canonical_address(&msg.multi_sig)?,
+        multi_sig: multi_sig.clone(),
+        multi_sig_code_hash: msg.multi_sig_code_hash,
         prng_seed,
         mint_cnt: 0,
+        prices: vec![Coin {
+            denom: "uscrt".to_string(),
+            amount: Uint128(1000000),
+        }],
+        discount: 0,
+        discount_ends: None,
+        revenue_split: vec![StoreRevenueShare {
+            address: multi_sig,
+            share: TOTAL_SHARES,
+        }],
+        token_prices: Vec::new(),
+        max_supply: None,
+        address_limit: None,
+        referral_share: 0,
+        pay_referrals_immediately: true,
+        mint_record_cnt: 0,
+        upgrade_record_cnt: 0,
+        fuse_cnt: 0,
+        migrate_cnt: 0,
+        minters: Vec::new(),
+        promo_cnt: 0,
+        max_packs_per_tx: 1,
+        elements: vec!["fire".to_string(), "water".to_string(), "earth".to_string()],
     };
     save(&mut deps.storage, CONFIG_KEY, &config)?;
+    let tiers = vec![StoredPackTier {
+        tier: PackTier {
+            name: "basic".to_string(),
+            price_bps: TOTAL_SHARES as u32,
+            rolls: 5,
+            aggregation: Aggregation::Min,
+            floor: 1,
+            cap: 100,
+            guaranteed_slots: 0,
+            guaranteed_floor: 1,
+            supply: None,
+        },
+        minted: 0,
+    }];
+    json_save(&mut deps.storage, TIERS_KEY, &tiers)?;
+    let recipes = vec![UpgradeRecipe {
+        id: 0,
+        burns: 2,
+        fee: None,
+        adjust: vec![
+            -2, -1, -1, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 2,
+        ],
+        brackets: vec![
+            PowerBracket {
+                above: 160,
+                base_upgrade: -1,
+            },
+            PowerBracket {
+                above: 80,
+                base_upgrade: 0,
+            },
+            PowerBracket {
+                above: 0,
+                base_upgrade: 1,
+            },
+            PowerBracket {
+                above: -81,
+                base_upgrade: 2,
+            },
+            PowerBracket {
+                above: -201,
+                base_upgrade: 3,
+            },
+        ],
+        default_upgrade: 4,
+        boosts: Vec::new(),
+    }];
+    json_save(&mut deps.storage, RECIPES_KEY, &recipes)?;
+    let name_rules = NameRules {
+        min_length: 1,
+        max_length: 32,
+        extra_chars: " -'.".to_string(),
+        unique: false,
+        rename_fee: Uint128(0),
+    };
+    save(&mut deps.storage, NAME_RULES_KEY, &name_rules)?;
 
     Ok(InitResponse {
         messages: vec![set_viewing_key_msg(
@@ -83,7 +187,12 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> HandleResult {
     let response = match msg {
-        HandleMsg::NewMultiSig { address } => try_new_multi_sig(deps, &env.message.sender, address),
+        HandleMsg::CreateViewingKey { entropy } => try_create_key(deps, env, &entropy),
+        HandleMsg::SetViewingKey { key, .. } => try_set_key(deps, env, key),
+        HandleMsg::RevokePermit { permit_name, .. } => try_revoke_permit(deps, env, &permit_name),
+        HandleMsg::NewMultiSig { address, code_hash } => {
+            try_new_multi_sig(deps, &env.message.sender, address, code_hash)
+        }
         HandleMsg::NewCardContract { card_contract } => {
             try_new_card_contract(deps, &env.message.sender, card_contract)
         }
@@ -94,7 +203,80 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             stop_mint,
             stop_upgrade,
         } => try_set_mint_status(deps, &env.message.sender, stop_mint, stop_upgrade),
-        HandleMsg::Mint { names } => try_mint(deps, env, names),
+        HandleMsg::SetPackPrices { prices } => {
+            try_set_pack_prices(deps, &env.message.sender, prices)
+        }
+        HandleMsg::SetTokenPrices { tokens } => try_set_token_prices(deps, &env, tokens),
+        HandleMsg::Receive {
+            from, amount, msg, ..
+        } => try_receive(deps, env, from, amount, msg),
+        HandleMsg::SetPackTier { tier } => try_set_pack_tier(deps, &env.message.sender, tier),
+        HandleMsg::RemovePackTier { name } => {
+            try_remove_pack_tier(deps, &env.message.sender, &name)
+        }
+        HandleMsg::SetUpgradeRecipe { recipe } => {
+            try_set_upgrade_recipe(deps, &env.message.sender, recipe)
+        }
+        HandleMsg::RemoveUpgradeRecipe { id } => {
+            try_remove_upgrade_recipe(deps, &env.message.sender, id)
+        }
+        HandleMsg::SetMinters { minters } => try_set_minters(deps, &env.message.sender, &minters),
+        HandleMsg::Airdrop { drops } => try_airdrop(deps, env, drops),
+        HandleMsg::AddPromoCodes { codes } => try_add_promo_codes(deps, &env.message.sender, codes),
+        HandleMsg::RedeemCode { code, names } => try_redeem_code(deps, env, &code, names),
+        HandleMsg::SetNameRules { rules } => try_set_name_rules(deps, &env.message.sender, rules),
+        HandleMsg::AddBannedSubstrings { substrings } => {
+            try_update_banned(deps, &env.message.sender, &substrings, true)
+        }
+        HandleMsg::RemoveBannedSubstrings { substrings } => {
+            try_update_banned(deps, &env.message.sender, &substrings, false)
+        }
+        HandleMsg::RenameHero { hero, name } => try_rename_hero(deps, env, hero, name),
+        HandleMsg::SetElements { elements } => {
+            try_set_elements(deps, &env.message.sender, elements)
+        }
+        HandleMsg::SetMintLimits {
+            max_supply,
+            address_limit,
+            max_packs_per_tx,
+        } => try_set_mint_limits(
+            deps,
+            &env.message.sender,
+            max_supply,
+            address_limit,
+            max_packs_per_tx,
+        ),
+        HandleMsg::SetSalePhases { phases } => {
+            try_set_sale_phases(deps, &env.message.sender, phases)
+        }
+        HandleMsg::AddToAllowlist { phase, addresses } => {
+            try_update_allowlist(deps, &env.message.sender, phase, &addresses, true)
+        }
+        HandleMsg::RemoveFromAllowlist { phase, addresses } => {
+            try_update_allowlist(deps, &env.message.sender, phase, &addresses, false)
+        }
+        HandleMsg::RegisterReferralCode { code } => {
+            try_register_referral_code(deps, &env.message.sender, code)
+        }
+        HandleMsg::ClaimReferralRewards {} => try_claim_referral_rewards(deps, env),
+        HandleMsg::SetReferralTerms {
+            share,
+            pay_immediately,
+        } => try_set_referral_terms(deps, &env.message.sender, share, pay_immediately),
+        HandleMsg::SetDiscount { discount, ends } => {
+            try_set_discount(deps, &env.message.sender, discount, ends)
+        }
+        HandleMsg::SetRevenueSplit { recipients } => {
+            try_set_revenue_split(deps, &env.message.sender, recipients)
+        }
+        HandleMsg::Mint {
+            names,
+            tier,
+            proof,
+            referral,
+            packs,
+            recipient,
+        } => try_mint(deps, env, names, tier, proof, referral, packs, recipient),
         HandleMsg::ChangeAdmin { address } => try_change_admin(deps, &env.message.sender, address),
         HandleMsg::AddMintCount { packs_minted } => {
             try_add_count(deps, &env.message.sender, packs_minted)
@@ -102,15 +284,101 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::Upgrade {
             burn,
             upgrade,
+            recipe,
             entropy,
-        } => try_upgrade(deps, env, burn, upgrade, &entropy),
+        } => try_upgrade(deps, env, burn, upgrade, recipe, &entropy),
+        HandleMsg::Fuse {
+            parents,
+            name,
+            entropy,
+        } => try_fuse(deps, env, parents, name, &entropy),
+        HandleMsg::MigrateHero { hero } => try_migrate_hero(deps, env, hero),
     };
     pad_handle_result(response, BLOCK_SIZE)
 }
 
 /// Returns HandleResult
 ///
-/// burn 2 heroes to upgrade a third
+/// creates a viewing key
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `entropy` - string slice of the input String to be used as entropy in randomization
+fn try_create_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    entropy: &str,
+) -> HandleResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let key = ViewingKey::new(&env, &config.prng_seed, entropy.as_ref());
+    let message_sender = &deps.api.canonical_address(&env.message.sender)?;
+    let mut key_store = PrefixedStorage::new(PREFIX_VIEW_KEY, &mut deps.storage);
+    save(&mut key_store, message_sender.as_slice(), &key.to_hashed())?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ViewingKey {
+            key: format!("{}", key),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// sets the viewing key to the input String
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `key` - String to be used as the viewing key
+fn try_set_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    key: String,
+) -> HandleResult {
+    let vk = ViewingKey(key.clone());
+    let message_sender = &deps.api.canonical_address(&env.message.sender)?;
+    let mut key_store = PrefixedStorage::new(PREFIX_VIEW_KEY, &mut deps.storage);
+    save(&mut key_store, message_sender.as_slice(), &vk.to_hashed())?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ViewingKey { key })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// revoke a query permit
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `permit_name` - name of the permit to revoke
+fn try_revoke_permit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    permit_name: &str,
+) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    revoke_permit(&mut deps.storage, &sender_raw, permit_name)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RevokePermit {
+            permit_name: permit_name.to_string(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// burn heroes to upgrade another
 ///
 /// # Arguments
 ///
@@ -118,26 +386,60 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 /// * `env` - the Env of contract's environment
 /// * `burn` - list of heroes to burn
 /// * `upgrade` - the hero to upgrade
+/// * `recipe` - optional id of the upgrade recipe to use
 /// * `entropy` - rng entropy string slice
 fn try_upgrade<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     burn: Vec<HeroInfo>,
     upgrade: HeroInfo,
+    recipe: Option<u32>,
     entropy: &str,
 ) -> HandleResult {
     let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
     if config.upgrade_halt {
         return Err(StdError::generic_err("Upgrades have been halted"));
     }
-    if burn.len() != 2 {
-        return Err(StdError::generic_err(
-            "You must burn exactly two heroes to do an upgrade",
-        ));
+    let recipes: Vec<UpgradeRecipe> = json_load(&deps.storage, RECIPES_KEY)?;
+    let recipe = find_recipe(&recipes, recipe)?;
+    if burn.len() != recipe.burns as usize {
+        return Err(StdError::generic_err(format!(
+            "Upgrade recipe {} requires burning exactly {} heroes",
+            recipe.id, recipe.burns
+        )));
+    }
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    if let Some(fee) = recipe.fee.filter(|f| f.u128() > 0) {
+        let required = Coin {
+            denom: "uscrt".to_string(),
+            amount: fee,
+        };
+        if env.message.sent_funds != [required.clone()] {
+            return Err(StdError::generic_err(format!(
+                "You must pay exactly {}uscrt to use upgrade recipe {}",
+                fee, recipe.id
+            )));
+        }
+        messages.extend(pay_revenue(
+            &deps.api,
+            &env,
+            &config,
+            required,
+            "upgrade fee",
+        )?);
+    } else if !env.message.sent_funds.is_empty() {
+        return Err(StdError::generic_err(format!(
+            "Upgrade recipe {} does not charge a fee",
+            recipe.id
+        )));
     }
+    let burned = burn.clone();
+    let upgraded = upgrade.clone();
     let mut version_burns: Vec<VersionBurn> = Vec::new();
     let mut prng = get_prng(&env, &config.prng_seed, entropy.as_ref());
     config.prng_seed = prng.rand_bytes().to_vec();
+    let record_id = config.upgrade_record_cnt;
+    config.upgrade_record_cnt += 1;
     save(&mut deps.storage, CONFIG_KEY, &config)?;
     let whitelist_err = format!(
         "This contract: {} has not been fully whitelisted on NFT contract:",
@@ -175,8 +477,9 @@ fn try_upgrade<S: Storage, A: Api, Q: Querier>(
                 .ok_or_else(|| StdError::generic_err("We just pushed so this is impossible"))?;
             &last.stored
         };
+        let hero_key = name_key(&stored_ci.address, &hero.token_id);
         // get the stats and sum the hero's skill points
-        let (stats, _m) = get_stats(
+        let (stats, burned_meta) = get_stats(
             &deps.querier,
             hero.token_id,
             viewer.clone(),
@@ -187,6 +490,10 @@ fn try_upgrade<S: Storage, A: Api, Q: Querier>(
             "burn",
         )?;
         burn_points += stats.current.iter().map(|u| *u as i16).sum::<i16>();
+        // burned heroes give up their names
+        if let Some(name) = burned_meta.name.as_ref() {
+            transfer_name(&mut deps.storage, name, &hero_key, None);
+        }
     }
 
     let stored_ci = if let Some(vburn) = version_burns
@@ -208,19 +515,21 @@ fn try_upgrade<S: Storage, A: Api, Q: Querier>(
         &whitelist_err,
         "upgrade",
     )?;
+    // leveled up heroes can upgrade past the base skill cap
+    let cap = stats.skill_cap();
     let pre_upgrade_skills = stats.current;
     let pre_sum = pre_upgrade_skills.iter().map(|u| *u as i16).sum::<i16>();
     // do the upgrade
-    let power_diff = 2 * pre_sum - burn_points;
-    let adjust: [i8; 23] = [
-        -2, -1, -1, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 2,
-    ];
+    let power_diff = recipe.burns as i16 * pre_sum - burn_points;
+    let mod_val = recipe.adjust.len();
+    // only use bytes below the largest multiple of the table size so every adjustment is
+    // equally likely
+    let limit = 256 - 256 % mod_val;
     let mut rand_iter = config.prng_seed.iter();
-    // find 4 bytes that are less than 253
     let mut upgrade_rand: Vec<u8> = Vec::new();
-    while upgrade_rand.len() < 4 {
+    while upgrade_rand.len() < pre_upgrade_skills.len() {
         if let Some(rdm) = rand_iter.next() {
-            if *rdm < 253 {
+            if (*rdm as usize) < limit {
                 upgrade_rand.push(*rdm);
             }
         } else {
@@ -229,41 +538,36 @@ fn try_upgrade<S: Storage, A: Api, Q: Querier>(
             rand_iter = config.prng_seed.iter();
         }
     }
-    let base_upgrade: i8 = if power_diff > 160 {
-        -1
-    } else if power_diff > 80 {
-        0
-    } else if power_diff > 0 {
-        1
-    } else if power_diff >= -80 {
-        2
-    } else if power_diff >= -200 {
-        3
-    } else {
-        4
-    };
+    let base_upgrade = get_base_upgrade(recipe, power_diff);
     let mut upgrade_iter = upgrade_rand.iter();
-    let mod_val = adjust.len();
     let post_upgrade_skills = pre_upgrade_skills
         .iter()
-        .map(|u| {
+        .enumerate()
+        .map(|(i, u)| {
             let r = upgrade_iter.next().ok_or_else(|| {
-                StdError::generic_err("Can not have less than 4 random upgrade bytes")
+                StdError::generic_err("Can not have less random upgrade bytes than skills")
             })?;
-            let modified = base_upgrade + adjust[(*r as usize) % mod_val];
-            if modified > 0 {
-                let new_skill = modified as u8 + u;
-                if new_skill > 100 {
-                    Ok(100)
-                } else {
-                    Ok(new_skill)
-                }
-            } else {
-                Ok(*u)
-            }
+            let adjust = recipe.adjust[(*r as usize) % mod_val];
+            Ok(upgrade_skill(recipe, base_upgrade, i, *u, adjust, cap))
         })
         .collect::<StdResult<Vec<u8>>>()?;
-    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let owner = deps.api.canonical_address(&env.message.sender)?;
+    let record = StoredUpgradeRecord {
+        owner,
+        time: env.block.time,
+        upgraded,
+        burned,
+        pre_upgrade_skills: pre_upgrade_skills.clone(),
+        post_upgrade_skills: post_upgrade_skills.clone(),
+    };
+    append_record(
+        &mut deps.storage,
+        PREFIX_UPGRADE_HISTORY,
+        PREFIX_UPGRADE_IDS,
+        record_id,
+        &record.owner,
+        &record,
+    )?;
     if pre_upgrade_skills != post_upgrade_skills {
         stats.current = post_upgrade_skills.clone();
         let stats_str = serde_json::to_string(&stats)
@@ -278,7 +582,7 @@ fn try_upgrade<S: Storage, A: Api, Q: Querier>(
             upgrade.contract_address,
         )?);
     }
-    // burn the other 2 heroes
+    // burn the other heroes
     for vburn in version_burns.into_iter() {
         messages.push(batch_burn_nft_msg(
             vec![vburn.burns],
@@ -300,81 +604,2411 @@ fn try_upgrade<S: Storage, A: Api, Q: Querier>(
 
 /// Returns HandleResult
 ///
-/// mint a pack of cards
+/// burn two heroes to mint a new hero descended from them
 ///
 /// # Arguments
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
-/// * `env` - Env of contract's environment
-/// * `names` - list of names for the newly minted cards
-fn try_mint<S: Storage, A: Api, Q: Querier>(
+/// * `env` - the Env of contract's environment
+/// * `parents` - the heroes to fuse
+/// * `name` - name to give the new hero
+/// * `entropy` - rng entropy string slice
+fn try_fuse<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    names: Vec<String>,
+    parents: Vec<HeroInfo>,
+    name: String,
+    entropy: &str,
 ) -> HandleResult {
     let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
-    if config.minting_halt {
-        return Err(StdError::generic_err(
-            "The minter has been stopped.  No new cards can be minted",
-        ));
+    if config.minting_halt || config.upgrade_halt {
+        return Err(StdError::generic_err("Fusion has been halted"));
+    }
+    if parents.len() != 2 {
+        return Err(StdError::generic_err("You must fuse exactly two heroes"));
     }
-    if env.message.sent_funds.len() != 1
-        || env.message.sent_funds[0].amount != Uint128(1000000)
-        || env.message.sent_funds[0].denom != *"uscrt"
+    if parents[0].token_id == parents[1].token_id
+        && parents[0].contract_address == parents[1].contract_address
     {
-        return Err(StdError::generic_err(
-            "You must pay exactly 1 SCRT to buy a pack of heroes",
-        ));
+        return Err(StdError::generic_err("A hero can not be fused with itself"));
     }
-    if names.len() < 3 {
-        return Err(StdError::generic_err(
-            "You must supply at least 3 names to mint 3 cards",
-        ));
+    let token_id = format!("fused{}", config.fuse_cnt);
+    let whitelist_err = format!(
+        "This contract: {} has not been fully whitelisted on NFT contract:",
+        &env.contract.address
+    );
+    let viewing_key: String = load(&deps.storage, VKEY_KEY)?;
+    let viewer = ViewerInfo {
+        address: env.contract.address.clone(),
+        viewing_key,
+    };
+    let mut version_burns: Vec<VersionBurn> = Vec::new();
+    let mut parent_skills: Vec<Vec<u8>> = Vec::new();
+    let mut parent_elements: Vec<Option<String>> = Vec::new();
+    let mut generation = 0u32;
+    for hero in parents.iter() {
+        let stored_ci = find_card_version(&deps.api, &config, &hero.contract_address, "fuse")?;
+        let (stats, parent_meta) = get_stats(
+            &deps.querier,
+            hero.token_id.clone(),
+            viewer.clone(),
+            stored_ci,
+            hero.contract_address.clone(),
+            &env.message.sender,
+            &whitelist_err,
+            "fuse",
+        )?;
+        // burned parents give up their names
+        if let Some(parent_name) = parent_meta.name.as_ref() {
+            transfer_name(
+                &mut deps.storage,
+                parent_name,
+                &name_key(&stored_ci.address, &hero.token_id),
+                None,
+            );
+        }
+        parent_skills.push(stats.base);
+        parent_elements.push(stats.element);
+        if let Some(lineage) = may_load_lineage(&deps.storage, &stored_ci.address, &hero.token_id)?
+        {
+            generation = generation.max(lineage.generation);
+        }
+        if let Some(vburn) = version_burns
+            .iter_mut()
+            .find(|v| v.human == hero.contract_address)
+        {
+            vburn.burns.token_ids.push(hero.token_id.clone());
+        } else {
+            version_burns.push(VersionBurn {
+                human: hero.contract_address.clone(),
+                burns: Burn {
+                    token_ids: vec![hero.token_id.clone()],
+                    memo: Some(format!("Burned to fuse token_id {}", &token_id)),
+                },
+                stored: stored_ci.clone(),
+            });
+        }
     }
-    let entropy = names.join("");
+    generation += 1;
+    let child_key = name_key(
+        &config
+            .card_versions
+            .last()
+            .ok_or_else(|| StdError::generic_err("Card version history is corrupt"))?
+            .address,
+        &token_id,
+    );
+    check_name(&mut deps.storage, &name, &child_key)?;
+    // only use bytes below the largest multiple of the mutation range so every mutation is
+    // equally likely
+    let range = 2 * FUSION_MUTATION as usize + 1;
+    let limit = 256 - 256 % range;
     let mut prng = get_prng(&env, &config.prng_seed, entropy.as_ref());
-    let rdm_bytes = prng.rand_bytes().to_vec();
-    let mut mints = Vec::new();
-
-    for (i, name) in names.into_iter().enumerate() {
-        if i > 2 {
-            break;
-        }
-        let start_byte = i * 20;
-        mints.push(get_mints(
-            &rdm_bytes[start_byte..start_byte + 20],
-            name,
-            &env.message.sender,
-        )?);
+    let mut mutations: Vec<i16> = Vec::new();
+    while mutations.len() < parent_skills[0].len() {
+        mutations.extend(
+            prng.rand_bytes()
+                .iter()
+                .filter(|b| (**b as usize) < limit)
+                .map(|b| (*b as usize % range) as i16 - FUSION_MUTATION as i16),
+        );
     }
-    config.prng_seed = rdm_bytes;
-    config.mint_cnt += 1;
+    let skills = parent_skills[0]
+        .iter()
+        .zip(parent_skills[1].iter())
+        .zip(mutations.iter())
+        .map(|((a, b), m)| ((*a as i16 + *b as i16) / 2 + m).clamp(1, 100) as u8)
+        .collect::<Vec<u8>>();
+    // the child inherits the element of a random parent, falling back to the other parent's,
+    // and rolls a new one if neither parent has an element
+    let rdm_bytes = prng.rand_bytes();
+    let first = (rdm_bytes[0] % 2) as usize;
+    let element = parent_elements[first]
+        .clone()
+        .or_else(|| parent_elements[1 - first].clone())
+        .or_else(|| pick_element(&config.elements, rdm_bytes[1]));
+    config.prng_seed = prng.rand_bytes().to_vec();
+    config.fuse_cnt += 1;
     save(&mut deps.storage, CONFIG_KEY, &config)?;
     let card_contract = config
         .card_versions
-        .pop()
+        .last()
         .ok_or_else(|| StdError::generic_err("Card version history is corrupt"))?;
+    save_lineage(
+        &mut deps.storage,
+        &card_contract.address,
+        &token_id,
+        &StoredLineage {
+            parents: parents.clone(),
+            generation,
+        },
+    )?;
+    let stats = Stats {
+        base: skills.clone(),
+        current: skills.clone(),
+        element,
+        xp: 0,
+        level: 0,
+    };
+    let stats_str = serde_json::to_string(&stats)
+        .map_err(|e| StdError::generic_err(format!("Error serializing card stats: {}", e)))?;
+    let lineage_str = serde_json::to_string(&Lineage {
+        generation,
+        parents,
+    })
+    .map_err(|e| StdError::generic_err(format!("Error serializing lineage: {}", e)))?;
+    let mint = Mint {
+        token_id: Some(token_id.clone()),
+        owner: Some(env.message.sender.clone()),
+        public_metadata: Some(Metadata {
+            name: Some(name.clone()),
+            description: None,
+            image: None,
+        }),
+        private_metadata: Some(Metadata {
+            name: Some(name),
+            description: Some(lineage_str),
+            image: Some(stats_str),
+        }),
+        memo: None,
+    };
     let mut messages: Vec<CosmosMsg> = Vec::new();
+    for vburn in version_burns.into_iter() {
+        messages.push(batch_burn_nft_msg(
+            vec![vburn.burns],
+            None,
+            BLOCK_SIZE,
+            vburn.stored.code_hash,
+            vburn.human,
+        )?);
+    }
     messages.push(batch_mint_nft_msg(
-        mints,
+        vec![mint],
         None,
         BLOCK_SIZE,
-        card_contract.code_hash,
+        card_contract.code_hash.clone(),
         deps.api.human_address(&card_contract.address)?,
     )?);
-    let amount: Vec<Coin> = vec![Coin {
-        denom: "uscrt".to_string(),
-        amount: Uint128(1000000),
-    }];
-    messages.push(CosmosMsg::Bank(BankMsg::Send {
-        from_address: env.contract.address,
-        to_address: deps.api.human_address(&config.multi_sig)?,
-        amount,
-    }));
     Ok(HandleResponse {
-        messages,
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Fuse {
+            token_id,
+            skills,
+            generation,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// burn a hero on a legacy card contract and mint the same hero on the current one
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - the Env of contract's environment
+/// * `hero` - the hero to migrate
+fn try_migrate_hero<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    hero: HeroInfo,
+) -> HandleResult {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if config.minting_halt {
+        return Err(StdError::generic_err("Migration has been halted"));
+    }
+    let stored_ci = find_card_version(&deps.api, &config, &hero.contract_address, "migrate")?;
+    let card_contract = config
+        .card_versions
+        .last()
+        .ok_or_else(|| StdError::generic_err("Card version history is corrupt"))?;
+    if stored_ci == card_contract {
+        return Err(StdError::generic_err(
+            "This hero is already on the current card contract",
+        ));
+    }
+    let whitelist_err = format!(
+        "This contract: {} has not been fully whitelisted on NFT contract:",
+        &env.contract.address
+    );
+    let viewing_key: String = load(&deps.storage, VKEY_KEY)?;
+    let viewer = ViewerInfo {
+        address: env.contract.address,
+        viewing_key,
+    };
+    // the stats and lineage are carried over in the private metadata
+    let (_stats, priv_meta) = get_stats(
+        &deps.querier,
+        hero.token_id.clone(),
+        viewer,
+        stored_ci,
+        hero.contract_address.clone(),
+        &env.message.sender,
+        &whitelist_err,
+        "migrate",
+    )?;
+    let migrated_to = HeroInfo {
+        token_id: format!("migrated{}", config.migrate_cnt),
+        contract_address: deps.api.human_address(&card_contract.address)?,
+    };
+    save_migration(
+        &mut deps.storage,
+        &stored_ci.address,
+        &hero.token_id,
+        &migrated_to,
+    )?;
+    // the migrated hero keeps its name
+    if let Some(name) = priv_meta.name.as_ref() {
+        transfer_name(
+            &mut deps.storage,
+            name,
+            &name_key(&stored_ci.address, &hero.token_id),
+            Some(&name_key(&card_contract.address, &migrated_to.token_id)),
+        );
+    }
+    // keep fused heroes connected to their ancestors
+    if let Some(lineage) = may_load_lineage(&deps.storage, &stored_ci.address, &hero.token_id)? {
+        save_lineage(
+            &mut deps.storage,
+            &card_contract.address,
+            &migrated_to.token_id,
+            &lineage,
+        )?;
+    }
+    let messages = vec![
+        batch_burn_nft_msg(
+            vec![Burn {
+                token_ids: vec![hero.token_id],
+                memo: Some(format!(
+                    "Burned to migrate to token_id {}",
+                    &migrated_to.token_id
+                )),
+            }],
+            None,
+            BLOCK_SIZE,
+            stored_ci.code_hash.clone(),
+            hero.contract_address,
+        )?,
+        batch_mint_nft_msg(
+            vec![Mint {
+                token_id: Some(migrated_to.token_id.clone()),
+                owner: Some(env.message.sender),
+                public_metadata: Some(Metadata {
+                    name: priv_meta.name.clone(),
+                    description: None,
+                    image: None,
+                }),
+                private_metadata: Some(priv_meta),
+                memo: None,
+            }],
+            None,
+            BLOCK_SIZE,
+            card_contract.code_hash.clone(),
+            migrated_to.contract_address.clone(),
+        )?,
+    ];
+    config.migrate_cnt += 1;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::MigrateHero { migrated_to })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// mint packs of cards
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `names` - list of names for the newly minted cards
+/// * `tier` - optional name of the pack tier to buy
+/// * `proof` - optional Merkle proof of the sender's address
+/// * `referral` - optional referral code
+/// * `packs` - optional number of packs to buy
+/// * `recipient` - optional address that will own the cards
+#[allow(clippy::too_many_arguments)]
+fn try_mint<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    names: Vec<String>,
+    tier: Option<String>,
+    proof: Option<Vec<Binary>>,
+    referral: Option<String>,
+    packs: Option<u32>,
+    recipient: Option<HumanAddr>,
+) -> HandleResult {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if config.minting_halt {
+        return Err(StdError::generic_err(
+            "The minter has been stopped.  No new cards can be minted",
+        ));
+    }
+    let packs = check_pack_count(&config, packs, &names)?;
+    let mut tiers: Vec<StoredPackTier> = json_load(&deps.storage, TIERS_KEY)?;
+    let tier_idx = find_tier(&tiers, tier.as_deref())?;
+    let phase_bps = claim_mint_allowance(
+        deps,
+        &config,
+        env.block.time,
+        &env.message.sender,
+        proof,
+        packs,
+    )?;
+    let price_bps = phase_price_bps(tiers[tier_idx].tier.price_bps, phase_bps);
+    let prices: Vec<Coin> = config
+        .current_prices(env.block.time, price_bps)
+        .into_iter()
+        .map(|p| Coin {
+            denom: p.denom,
+            amount: Uint128(p.amount.u128() * packs as u128),
+        })
+        .collect();
+    if env.message.sent_funds.len() != 1 || !prices.contains(&env.message.sent_funds[0]) {
+        let accepted = prices
+            .iter()
+            .map(|p| format!("{}{}", p.amount, p.denom))
+            .collect::<Vec<String>>()
+            .join(" or ");
+        return Err(StdError::generic_err(format!(
+            "You must pay exactly {} to buy {} pack(s) of heroes",
+            accepted, packs
+        )));
+    }
+    let mut payment = env.message.sent_funds[0].clone();
+    let price = StoredPayment {
+        amount: Uint128(payment.amount.u128() / packs as u128),
+        denom: Some(payment.denom.clone()),
+        token: None,
+    };
+    let owner = recipient.unwrap_or_else(|| env.message.sender.clone());
+    let mut mints: Vec<Mint> = Vec::new();
+    for pack_names in names.chunks(3).take(packs as usize) {
+        mints.extend(mint_pack(
+            deps,
+            &env,
+            &mut config,
+            &mut tiers[tier_idx],
+            pack_names.to_vec(),
+            &owner,
+            price.clone(),
+        )?);
+    }
+    let mut messages = vec![mint_msg(&deps.api, &config, mints)?];
+    json_save(&mut deps.storage, TIERS_KEY, &tiers)?;
+    if let Some(code) = referral {
+        let (cut, pay_to) = credit_referral(
+            deps,
+            &config,
+            &code,
+            &env.message.sender,
+            payment.amount,
+            Some(&payment.denom),
+            None,
+            packs,
+        )?;
+        payment.amount = Uint128(payment.amount.u128() - cut.u128());
+        if let Some(referrer) = pay_to {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: referrer,
+                amount: vec![Coin {
+                    denom: payment.denom.clone(),
+                    amount: cut,
+                }],
+            }));
+        }
+    }
+    messages.extend(pay_revenue(&deps.api, &env, &config, payment, "pack sale")?);
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Mint { status: Success })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// mint a pack of cards paid for with a SNIP-20 token
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `from` - owner of the tokens sent
+/// * `amount` - amount of tokens sent
+/// * `msg` - optional base64 encoded ReceiveMsg
+fn try_receive<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> HandleResult {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if config.minting_halt {
+        return Err(StdError::generic_err(
+            "The minter has been stopped.  No new cards can be minted",
+        ));
+    }
+    let (names, tier, proof, referral, packs, recipient) = match msg {
+        Some(bin) => match from_binary(&bin)? {
+            ReceiveMsg::Mint {
+                names,
+                tier,
+                proof,
+                referral,
+                packs,
+                recipient,
+            } => (names, tier, proof, referral, packs, recipient),
+        },
+        None => {
+            return Err(StdError::generic_err(
+                "You must supply a Mint msg with the names of the cards",
+            ))
+        }
+    };
+    let packs = check_pack_count(&config, packs, &names)?;
+    let mut tiers: Vec<StoredPackTier> = json_load(&deps.storage, TIERS_KEY)?;
+    let tier_idx = find_tier(&tiers, tier.as_deref())?;
+    let phase_bps = claim_mint_allowance(deps, &config, env.block.time, &from, proof, packs)?;
+    let price_bps = phase_price_bps(tiers[tier_idx].tier.price_bps, phase_bps);
+    let token_raw = deps.api.canonical_address(&env.message.sender)?;
+    let price = config
+        .current_token_price(&token_raw, env.block.time, price_bps)
+        .ok_or_else(|| {
+            StdError::generic_err(format!("{} is not an accepted token", env.message.sender))
+        })?;
+    let total = Uint128(price.u128() * packs as u128);
+    if amount != total {
+        return Err(StdError::generic_err(format!(
+            "You must pay exactly {} tokens to buy {} pack(s) of heroes",
+            total, packs
+        )));
+    }
+    let token = config
+        .token_prices
+        .iter()
+        .find(|t| t.contract.address == token_raw)
+        .ok_or_else(|| StdError::generic_err("Token is not accepted"))?
+        .contract
+        .clone();
+    let paid = StoredPayment {
+        amount: price,
+        denom: None,
+        token: Some(token_raw),
+    };
+    let owner = recipient.unwrap_or_else(|| from.clone());
+    let mut mints: Vec<Mint> = Vec::new();
+    for pack_names in names.chunks(3).take(packs as usize) {
+        mints.extend(mint_pack(
+            deps,
+            &env,
+            &mut config,
+            &mut tiers[tier_idx],
+            pack_names.to_vec(),
+            &owner,
+            paid.clone(),
+        )?);
+    }
+    let mut messages = vec![mint_msg(&deps.api, &config, mints)?];
+    json_save(&mut deps.storage, TIERS_KEY, &tiers)?;
+    let mut payment = amount;
+    if let Some(code) = referral {
+        let (cut, pay_to) = credit_referral(
+            deps,
+            &config,
+            &code,
+            &from,
+            amount,
+            None,
+            Some(&token),
+            packs,
+        )?;
+        payment = Uint128(payment.u128() - cut.u128());
+        if let Some(referrer) = pay_to {
+            messages.push(transfer_msg(
+                referrer,
+                cut,
+                None,
+                BLOCK_SIZE,
+                token.code_hash.clone(),
+                env.message.sender.clone(),
+            )?);
+        }
+    }
+    messages.extend(pay_token_revenue(&deps.api, &config, &token, payment)?);
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Mint { status: Success })?),
+    })
+}
+
+/// Returns StdResult<u32> of the active sale phase's price in basis points of the pack tier
+/// price after verifying the buyer may mint the packs and counting the mints against their
+/// limits
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `config` - a reference to the Config
+/// * `time` - current time in seconds since epoch time 01/01/1970
+/// * `buyer` - a reference to the address buying the packs
+/// * `proof` - optional Merkle proof of the buyer's address
+/// * `packs` - number of packs being bought
+fn claim_mint_allowance<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    config: &Config,
+    time: u64,
+    buyer: &HumanAddr,
+    proof: Option<Vec<Binary>>,
+    packs: u32,
+) -> StdResult<u32> {
+    if let Some(max) = config.max_supply {
+        if config.mint_cnt >= max {
+            return Err(StdError::generic_err("All packs have been minted"));
+        }
+        if config.mint_cnt + packs > max {
+            return Err(StdError::generic_err(format!(
+                "Only {} packs are left",
+                max - config.mint_cnt
+            )));
+        }
+    }
+    let buyer_raw = deps.api.canonical_address(buyer)?;
+    let mut cnt_store = PrefixedStorage::new(PREFIX_MINT_COUNTS, &mut deps.storage);
+    let minted: u32 = may_load(&cnt_store, buyer_raw.as_slice())?.unwrap_or(0);
+    if let Some(limit) = config.address_limit {
+        if minted + packs > limit {
+            return Err(StdError::generic_err(format!(
+                "An address can only mint {} packs",
+                limit
+            )));
+        }
+    }
+    save(&mut cnt_store, buyer_raw.as_slice(), &(minted + packs))?;
+    let phases: Vec<SalePhase> = json_may_load(&deps.storage, PHASES_KEY)?.unwrap_or_default();
+    // without sale phases the sale is always open
+    if phases.is_empty() {
+        return Ok(TOTAL_SHARES as u32);
+    }
+    let phase = phases
+        .iter()
+        .find(|p| p.start <= time && p.end.map_or(true, |e| time < e))
+        .ok_or_else(|| StdError::generic_err("There is no sale phase currently active"))?;
+    match &phase.access {
+        PhaseAccess::Public => (),
+        PhaseAccess::Allowlist => {
+            let allow_store = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_ALLOWLIST, phase.name.as_bytes()],
+                &deps.storage,
+            );
+            if allow_store.get(buyer_raw.as_slice()).is_none() {
+                return Err(StdError::generic_err(format!(
+                    "{} is not on the {} allowlist",
+                    buyer, phase.name
+                )));
+            }
+        }
+        PhaseAccess::Merkle { root } => {
+            let proof = proof.unwrap_or_default();
+            if !verify_merkle_proof(buyer, &proof, root) {
+                return Err(StdError::generic_err(format!(
+                    "Invalid proof that {} is on the {} allowlist",
+                    buyer, phase.name
+                )));
+            }
+        }
+    }
+    let mut phase_store = PrefixedStorage::multilevel(
+        &[PREFIX_PHASE_MINTS, phase.name.as_bytes()],
+        &mut deps.storage,
+    );
+    let phase_minted: u32 = may_load(&phase_store, buyer_raw.as_slice())?.unwrap_or(0);
+    if let Some(limit) = phase.address_limit {
+        if phase_minted + packs > limit {
+            return Err(StdError::generic_err(format!(
+                "An address can only mint {} packs during the {} phase",
+                limit, phase.name
+            )));
+        }
+    }
+    save(
+        &mut phase_store,
+        buyer_raw.as_slice(),
+        &(phase_minted + packs),
+    )?;
+    Ok(phase.price_bps)
+}
+
+/// Returns bool true if the proof shows the address is a leaf of the Merkle tree
+///
+/// # Arguments
+///
+/// * `address` - a reference to the address to verify
+/// * `proof` - the sibling hashes from the leaf to the root
+/// * `root` - a reference to the Merkle root
+fn verify_merkle_proof(address: &HumanAddr, proof: &[Binary], root: &Binary) -> bool {
+    let mut hash = sha_256(address.0.as_bytes()).to_vec();
+    for sibling in proof.iter() {
+        let sibling = sibling.as_slice();
+        hash = if hash.as_slice() <= sibling {
+            sha_256(&[hash.as_slice(), sibling].concat()).to_vec()
+        } else {
+            sha_256(&[sibling, hash.as_slice()].concat()).to_vec()
+        };
+    }
+    hash.as_slice() == root.as_slice()
+}
+
+/// Returns u32 of a pack tier's price in basis points of the pack prices during a sale phase
+///
+/// # Arguments
+///
+/// * `tier_bps` - price of the pack tier in basis points of the pack prices
+/// * `phase_bps` - price during the sale phase in basis points of the pack tier price
+fn phase_price_bps(tier_bps: u32, phase_bps: u32) -> u32 {
+    (tier_bps as u64 * phase_bps as u64 / TOTAL_SHARES as u64) as u32
+}
+
+/// Returns StdResult<(Uint128, Option<HumanAddr>)> of the referrer's cut of a payment, and
+/// the referrer's address if the cut should be paid immediately
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `config` - a reference to the Config
+/// * `code` - the referral code
+/// * `buyer` - a reference to the address buying the pack
+/// * `payment` - the amount paid for the packs
+/// * `denom` - native coin denomination of the payment.  None if paid with a SNIP-20 token
+/// * `token` - SNIP-20 token contract of the payment.  None if paid with a native coin
+/// * `packs` - number of packs bought
+#[allow(clippy::too_many_arguments)]
+fn credit_referral<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    config: &Config,
+    code: &str,
+    buyer: &HumanAddr,
+    payment: Uint128,
+    denom: Option<&str>,
+    token: Option<&StoreContractInfo>,
+    packs: u32,
+) -> StdResult<(Uint128, Option<HumanAddr>)> {
+    let code_store = ReadonlyPrefixedStorage::new(PREFIX_REFERRAL_CODES, &deps.storage);
+    let referrer_raw: CanonicalAddr = may_load(&code_store, code.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("{} is not a referral code", code)))?;
+    if referrer_raw == deps.api.canonical_address(buyer)? {
+        return Err(StdError::generic_err(
+            "You can not use your own referral code",
+        ));
+    }
+    let cut = Uint128(payment.u128() * config.referral_share as u128 / TOTAL_SHARES as u128);
+    let mut ref_store = PrefixedStorage::new(PREFIX_REFERRERS, &mut deps.storage);
+    let mut referrer: StoredReferrer = load(&ref_store, referrer_raw.as_slice())?;
+    referrer.packs += packs;
+    let pos = if let Some(pos) = referrer.rewards.iter().position(|r| {
+        r.denom.as_deref() == denom
+            && r.token.as_ref().map(|t| &t.address) == token.map(|t| &t.address)
+    }) {
+        pos
+    } else {
+        referrer.rewards.push(StoredReward {
+            denom: denom.map(String::from),
+            token: token.cloned(),
+            earned: Uint128(0),
+            unclaimed: Uint128(0),
+        });
+        referrer.rewards.len() - 1
+    };
+    referrer.rewards[pos].earned += cut;
+    if !config.pay_referrals_immediately {
+        referrer.rewards[pos].unclaimed += cut;
+    }
+    save(&mut ref_store, referrer_raw.as_slice(), &referrer)?;
+    let pay_to = if config.pay_referrals_immediately && cut != Uint128(0) {
+        Some(deps.api.human_address(&referrer_raw)?)
+    } else {
+        None
+    };
+    Ok((cut, pay_to))
+}
+
+/// Returns StdResult<u32> of the number of packs being bought after verifying it is allowed and
+/// enough names were supplied
+///
+/// # Arguments
+///
+/// * `config` - a reference to the Config
+/// * `packs` - optional number of packs to buy
+/// * `names` - names for the newly minted cards
+fn check_pack_count(config: &Config, packs: Option<u32>, names: &[String]) -> StdResult<u32> {
+    let packs = packs.unwrap_or(1);
+    if packs == 0 || packs > config.max_packs_per_tx {
+        return Err(StdError::generic_err(format!(
+            "You can buy from 1 to {} packs in one transaction",
+            config.max_packs_per_tx
+        )));
+    }
+    if names.len() < 3 * packs as usize {
+        return Err(StdError::generic_err(format!(
+            "You must supply at least {} names to mint {} cards",
+            3 * packs,
+            3 * packs
+        )));
+    }
+    Ok(packs)
+}
+
+/// Returns String of a name in lowercase without any characters that are not letters or digits
+///
+/// # Arguments
+///
+/// * `name` - the name to normalize
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Returns StdResult<()> after verifying a hero name follows the name rules and registering it
+/// to the hero if names must be unique
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `name` - the name to check
+/// * `hero_key` - the registry key of the hero taking the name
+fn check_name<S: Storage>(storage: &mut S, name: &str, hero_key: &[u8]) -> StdResult<()> {
+    let rules: NameRules = load(storage, NAME_RULES_KEY)?;
+    let len = name.chars().count();
+    if len < rules.min_length as usize || len > rules.max_length as usize {
+        return Err(StdError::generic_err(format!(
+            "Hero names must be from {} to {} characters long",
+            rules.min_length, rules.max_length
+        )));
+    }
+    if let Some(bad) = name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !rules.extra_chars.contains(*c))
+    {
+        return Err(StdError::generic_err(format!(
+            "Hero names can not contain the character {:?}",
+            bad
+        )));
+    }
+    let normalized = normalize_name(name);
+    let banned: Vec<String> = may_load(storage, BANNED_KEY)?.unwrap_or_default();
+    if banned.iter().any(|b| normalized.contains(b.as_str())) {
+        return Err(StdError::generic_err(format!(
+            "The name {} is not allowed",
+            name
+        )));
+    }
+    if rules.unique {
+        let mut name_store = PrefixedStorage::new(PREFIX_NAMES, storage);
+        if name_store.get(normalized.as_bytes()).is_some() {
+            return Err(StdError::generic_err(format!(
+                "The name {} is already taken",
+                name
+            )));
+        }
+        name_store.set(normalized.as_bytes(), hero_key);
+    }
+    Ok(())
+}
+
+/// Returns Vec<u8> of the key that identifies a hero as the holder of a registered name
+///
+/// # Arguments
+///
+/// * `contract` - a reference to the card contract's address
+/// * `token_id` - the hero's token id
+fn name_key(contract: &CanonicalAddr, token_id: &str) -> Vec<u8> {
+    [contract.as_slice(), token_id.as_bytes()].concat()
+}
+
+/// Moves a registered name from one hero to another, or releases it if there is no new holder.
+/// Nothing is changed if the name is not registered to the previous holder
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `name` - the registered name
+/// * `from` - the registry key of the hero that held the name
+/// * `to` - the optional registry key of the hero that now holds the name
+fn transfer_name<S: Storage>(storage: &mut S, name: &str, from: &[u8], to: Option<&[u8]>) {
+    let mut name_store = PrefixedStorage::new(PREFIX_NAMES, storage);
+    let normalized = normalize_name(name);
+    if name_store.get(normalized.as_bytes()).as_deref() == Some(from) {
+        if let Some(key) = to {
+            name_store.set(normalized.as_bytes(), key);
+        } else {
+            name_store.remove(normalized.as_bytes());
+        }
+    }
+}
+
+/// Returns StdResult<usize> of the index of the requested pack tier
+///
+/// # Arguments
+///
+/// * `tiers` - a reference to the list of pack tiers
+/// * `name` - optional name of the tier.  Defaults to the first tier
+fn find_tier(tiers: &[StoredPackTier], name: Option<&str>) -> StdResult<usize> {
+    if let Some(nm) = name {
+        tiers
+            .iter()
+            .position(|t| t.tier.name == nm)
+            .ok_or_else(|| StdError::generic_err(format!("There is no pack tier named {}", nm)))
+    } else if tiers.is_empty() {
+        Err(StdError::generic_err("There are no pack tiers for sale"))
+    } else {
+        Ok(0)
+    }
+}
+
+/// Returns StdResult<&UpgradeRecipe> of the requested upgrade recipe
+///
+/// # Arguments
+///
+/// * `recipes` - a reference to the list of upgrade recipes
+/// * `id` - optional id of the recipe.  Defaults to the first recipe
+fn find_recipe(recipes: &[UpgradeRecipe], id: Option<u32>) -> StdResult<&UpgradeRecipe> {
+    if let Some(id) = id {
+        recipes
+            .iter()
+            .find(|r| r.id == id)
+            .ok_or_else(|| StdError::generic_err(format!("There is no upgrade recipe {}", id)))
+    } else {
+        recipes
+            .first()
+            .ok_or_else(|| StdError::generic_err("There are no upgrade recipes"))
+    }
+}
+
+/// Returns i8 of the number of points a recipe adds to every skill for a power difference
+///
+/// # Arguments
+///
+/// * `recipe` - a reference to the upgrade recipe
+/// * `power_diff` - the power difference between the upgraded and burned heroes
+fn get_base_upgrade(recipe: &UpgradeRecipe, power_diff: i16) -> i8 {
+    recipe
+        .brackets
+        .iter()
+        .find(|b| power_diff > b.above)
+        .map_or(recipe.default_upgrade, |b| b.base_upgrade)
+}
+
+/// Returns i16 of the extra points a recipe adds to a skill
+///
+/// # Arguments
+///
+/// * `recipe` - a reference to the upgrade recipe
+/// * `skill` - index of the skill
+fn get_boost(recipe: &UpgradeRecipe, skill: usize) -> i16 {
+    recipe
+        .boosts
+        .iter()
+        .filter(|b| b.skill as usize == skill)
+        .map(|b| b.boost as i16)
+        .sum()
+}
+
+/// Returns u8 of a skill's value after an upgrade
+///
+/// # Arguments
+///
+/// * `recipe` - a reference to the upgrade recipe
+/// * `base_upgrade` - number of points the recipe adds to every skill
+/// * `skill` - index of the skill
+/// * `value` - skill's value before the upgrade
+/// * `adjust` - the adjustment rolled for this skill
+/// * `cap` - highest value the skill can reach at the hero's level
+fn upgrade_skill(
+    recipe: &UpgradeRecipe,
+    base_upgrade: i8,
+    skill: usize,
+    value: u8,
+    adjust: i8,
+    cap: u8,
+) -> u8 {
+    let modified = base_upgrade as i16 + adjust as i16 + get_boost(recipe, skill);
+    if modified > 0 {
+        std::cmp::min(value as i16 + modified, cap as i16) as u8
+    } else {
+        value
+    }
+}
+
+/// Returns StdResult<Vec<Mint>> of the mints of a pack of up to 3 cards
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `config` - a mutable reference to the Config
+/// * `stored_tier` - a mutable reference to the tier of the pack
+/// * `names` - list of names for the newly minted cards.  One card is minted for each of the
+///             first 3 names
+/// * `owner` - a reference to the address that will own the cards
+/// * `price` - the price paid for the pack
+fn mint_pack<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    config: &mut Config,
+    stored_tier: &mut StoredPackTier,
+    names: Vec<String>,
+    owner: &HumanAddr,
+    price: StoredPayment,
+) -> StdResult<Vec<Mint>> {
+    let cards = names.len().min(3);
+    if cards == 0 {
+        return Err(StdError::generic_err("A pack must contain at least 1 card"));
+    }
+    let card_contract = config
+        .card_versions
+        .last()
+        .ok_or_else(|| StdError::generic_err("Card version history is corrupt"))?
+        .address
+        .clone();
+    // token ids are assigned here so the name registry knows which hero holds each name
+    let token_ids: Vec<String> = (0..cards)
+        .map(|i| format!("pack{}-{}", config.mint_cnt, i))
+        .collect();
+    for (name, token_id) in names.iter().zip(token_ids.iter()) {
+        check_name(&mut deps.storage, name, &name_key(&card_contract, token_id))?;
+    }
+    if stored_tier
+        .tier
+        .supply
+        .map_or(false, |s| stored_tier.minted >= s)
+    {
+        return Err(StdError::generic_err(format!(
+            "The {} packs have sold out",
+            stored_tier.tier.name
+        )));
+    }
+    stored_tier.minted += 1;
+    let tier = &stored_tier.tier;
+    let entropy = names.join("");
+    let mut prng = get_prng(env, &config.prng_seed, entropy.as_ref());
+    // one extra byte per card to select its element
+    let bytes_per_card = 4 * tier.rolls as usize + 1;
+    let mut rdm_bytes: Vec<u8> = Vec::new();
+    while rdm_bytes.len() < cards * bytes_per_card {
+        rdm_bytes.extend_from_slice(&prng.rand_bytes());
+    }
+    let mut mints = Vec::new();
+    let record = StoredMintRecord {
+        owner: deps.api.canonical_address(owner)?,
+        time: env.block.time,
+        tier: tier.name.clone(),
+        names: names.iter().take(cards).cloned().collect(),
+        price,
+    };
+    append_record(
+        &mut deps.storage,
+        PREFIX_MINT_HISTORY,
+        PREFIX_MINT_IDS,
+        config.mint_record_cnt,
+        &record.owner,
+        &record,
+    )?;
+    config.mint_record_cnt += 1;
+
+    for (i, (name, token_id)) in names.into_iter().zip(token_ids.into_iter()).enumerate() {
+        let start_byte = i * bytes_per_card;
+        // the last cards of the pack fill the guaranteed slots
+        let guaranteed = i + tier.guaranteed_slots as usize >= cards;
+        mints.push(get_mints(
+            &rdm_bytes[start_byte..start_byte + bytes_per_card],
+            tier,
+            &config.elements,
+            guaranteed,
+            token_id,
+            name,
+            owner,
+        )?);
+    }
+    config.prng_seed = prng.rand_bytes().to_vec();
+    config.mint_cnt += 1;
+    save(&mut deps.storage, CONFIG_KEY, config)?;
+    Ok(mints)
+}
+
+/// Returns StdResult<CosmosMsg> of the message that mints cards on the current card contract
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `config` - a reference to the Config
+/// * `mints` - the cards to mint
+fn mint_msg<A: Api>(api: &A, config: &Config, mints: Vec<Mint>) -> StdResult<CosmosMsg> {
+    let card_contract = config
+        .card_versions
+        .last()
+        .ok_or_else(|| StdError::generic_err("Card version history is corrupt"))?;
+    batch_mint_nft_msg(
+        mints,
+        None,
+        BLOCK_SIZE,
+        card_contract.code_hash.clone(),
+        api.human_address(&card_contract.address)?,
+    )
+}
+
+/// Returns Vec<u128> of each revenue recipient's portion of a payment.  Any rounding dust goes
+/// to the first recipient
+///
+/// # Arguments
+///
+/// * `config` - a reference to the Config
+/// * `total` - the amount to split
+fn split_revenue(config: &Config, total: u128) -> Vec<u128> {
+    let mut dust = total;
+    let mut amounts: Vec<u128> = config
+        .revenue_split
+        .iter()
+        .map(|r| {
+            let amount = total * r.share as u128 / TOTAL_SHARES as u128;
+            dust -= amount;
+            amount
+        })
+        .collect();
+    if let Some(first) = amounts.first_mut() {
+        *first += dust;
+    }
+    amounts
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the messages that split a SNIP-20 payment among the
+/// revenue recipients
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `config` - a reference to the Config
+/// * `token` - a reference to the token contract
+/// * `payment` - the amount of tokens to split
+fn pay_token_revenue<A: Api>(
+    api: &A,
+    config: &Config,
+    token: &StoreContractInfo,
+    payment: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    let contract = token.get_humanized(api)?;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let amounts = split_revenue(config, payment.u128());
+    for (recipient, amount) in config.revenue_split.iter().zip(amounts.into_iter()) {
+        if amount == 0 {
+            continue;
+        }
+        messages.push(transfer_msg(
+            api.human_address(&recipient.address)?,
+            Uint128(amount),
+            None,
+            BLOCK_SIZE,
+            contract.code_hash.clone(),
+            contract.address.clone(),
+        )?);
+    }
+    Ok(messages)
+}
+
+/// Returns StdResult<Vec<CosmosMsg>> of the messages that split a payment among the revenue
+/// recipients
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `env` - a reference to the Env of contract's environment
+/// * `config` - a reference to the Config
+/// * `payment` - the Coin to split
+/// * `memo` - memo for deposits made through the multi sig
+fn pay_revenue<A: Api>(
+    api: &A,
+    env: &Env,
+    config: &Config,
+    payment: Coin,
+    memo: &str,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let amounts = split_revenue(config, payment.amount.u128());
+    for (recipient, amount) in config.revenue_split.iter().zip(amounts.into_iter()) {
+        if amount == 0 {
+            continue;
+        }
+        let address = api.human_address(&recipient.address)?;
+        // deposit scrt through the multi sig if possible so the payment shows up in its ledger
+        if let Some(code_hash) = config
+            .multi_sig_code_hash
+            .as_ref()
+            .filter(|_| recipient.address == config.multi_sig && payment.denom == "uscrt")
+        {
+            messages.push(
+                MultiSigHandleMsg::Deposit {
+                    memo: Some(memo.to_string()),
+                }
+                .to_cosmos_msg(
+                    code_hash.clone(),
+                    address,
+                    Some(Uint128(amount)),
+                )?,
+            );
+        } else {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: address,
+                amount: vec![Coin {
+                    denom: payment.denom.clone(),
+                    amount: Uint128(amount),
+                }],
+            }));
+        }
+    }
+    Ok(messages)
+}
+
+/// Returns HandleResult
+///
+/// set the accepted payments for a pack
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `prices` - full price of a pack in each accepted denomination
+fn try_set_pack_prices<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    prices: Vec<Coin>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if prices.is_empty() {
+        return Err(StdError::generic_err(
+            "At least one denomination must be accepted",
+        ));
+    }
+    for (i, price) in prices.iter().enumerate() {
+        if price.amount == Uint128(0) {
+            return Err(StdError::generic_err(format!(
+                "The {} price can not be zero",
+                price.denom
+            )));
+        }
+        if prices.iter().skip(i + 1).any(|p| p.denom == price.denom) {
+            return Err(StdError::generic_err(format!(
+                "{} is listed more than once",
+                price.denom
+            )));
+        }
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    config.prices = prices;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetPackPrices {
+            prices: config.prices,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the SNIP-20 tokens accepted as payment for a pack
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `tokens` - full price of a pack in each accepted token
+fn try_set_token_prices<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    tokens: Vec<TokenPrice>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let mut token_prices: Vec<StoreTokenPrice> = Vec::new();
+    for token in tokens.iter() {
+        if token.price == Uint128(0) {
+            return Err(StdError::generic_err(format!(
+                "The price in token {} can not be zero",
+                token.contract.address
+            )));
+        }
+        let contract = token.contract.get_store(&deps.api)?;
+        if token_prices
+            .iter()
+            .any(|t| t.contract.address == contract.address)
+        {
+            return Err(StdError::generic_err(format!(
+                "Token {} is listed more than once",
+                token.contract.address
+            )));
+        }
+        // only register with tokens that were not already accepted
+        if !config.token_prices.iter().any(|t| t.contract == contract) {
+            messages.push(register_receive_msg(
+                env.contract_code_hash.clone(),
+                None,
+                BLOCK_SIZE,
+                token.contract.code_hash.clone(),
+                token.contract.address.clone(),
+            )?);
+        }
+        token_prices.push(StoreTokenPrice {
+            contract,
+            price: token.price,
+        });
+    }
+    config.token_prices = token_prices;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetTokenPrices { tokens })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// add a pack tier or replace the tier with the same name
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `tier` - the tier's parameters
+fn try_set_pack_tier<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    tier: PackTier,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if tier.name.is_empty() {
+        return Err(StdError::generic_err("A pack tier must have a name"));
+    }
+    if tier.price_bps == 0 {
+        return Err(StdError::generic_err("A pack tier's price can not be zero"));
+    }
+    if tier.rolls == 0 || tier.rolls > 10 {
+        return Err(StdError::generic_err(
+            "A pack tier must roll each skill from 1 to 10 times",
+        ));
+    }
+    if tier.floor == 0 || tier.floor > tier.cap || tier.cap > 100 {
+        return Err(StdError::generic_err(
+            "Skill floor and cap must satisfy 1 <= floor <= cap <= 100",
+        ));
+    }
+    if tier.guaranteed_slots > 3 {
+        return Err(StdError::generic_err(
+            "A pack only has 3 slots to guarantee",
+        ));
+    }
+    if tier.guaranteed_slots > 0 && tier.guaranteed_floor > tier.cap {
+        return Err(StdError::generic_err(
+            "The guaranteed floor can not exceed the skill cap",
+        ));
+    }
+    let mut tiers: Vec<StoredPackTier> = json_load(&deps.storage, TIERS_KEY)?;
+    if let Some(existing) = tiers.iter_mut().find(|t| t.tier.name == tier.name) {
+        existing.tier = tier.clone();
+    } else {
+        tiers.push(StoredPackTier {
+            tier: tier.clone(),
+            minted: 0,
+        });
+    }
+    json_save(&mut deps.storage, TIERS_KEY, &tiers)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetPackTier { tier })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// remove a pack tier
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `name` - name of the tier to remove
+fn try_remove_pack_tier<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    name: &str,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let mut tiers: Vec<StoredPackTier> = json_load(&deps.storage, TIERS_KEY)?;
+    let len = tiers.len();
+    tiers.retain(|t| t.tier.name != name);
+    if tiers.len() == len {
+        return Err(StdError::generic_err(format!(
+            "There is no pack tier named {}",
+            name
+        )));
+    }
+    json_save(&mut deps.storage, TIERS_KEY, &tiers)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemovePackTier {
+            name: name.to_string(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// add an upgrade recipe or replace the recipe with the same id
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `recipe` - the recipe's parameters
+fn try_set_upgrade_recipe<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    mut recipe: UpgradeRecipe,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if recipe.burns == 0 || recipe.burns > 5 {
+        return Err(StdError::generic_err(
+            "An upgrade recipe must burn from 1 to 5 heroes",
+        ));
+    }
+    if recipe.adjust.is_empty() || recipe.adjust.len() > 256 {
+        return Err(StdError::generic_err(
+            "An upgrade recipe's adjust table must have from 1 to 256 entries",
+        ));
+    }
+    if recipe.boosts.iter().any(|b| b.skill > 3) {
+        return Err(StdError::generic_err(
+            "Heroes only have 4 skills.  Boosted skill indices must be from 0 to 3",
+        ));
+    }
+    // the first bracket the power difference is above is used, so check the highest first
+    recipe.brackets.sort_by(|a, b| b.above.cmp(&a.above));
+    if recipe.brackets.windows(2).any(|w| w[0].above == w[1].above) {
+        return Err(StdError::generic_err(
+            "An upgrade recipe can not have two brackets with the same threshold",
+        ));
+    }
+    let mut recipes: Vec<UpgradeRecipe> = json_load(&deps.storage, RECIPES_KEY)?;
+    if let Some(existing) = recipes.iter_mut().find(|r| r.id == recipe.id) {
+        *existing = recipe.clone();
+    } else {
+        recipes.push(recipe.clone());
+    }
+    json_save(&mut deps.storage, RECIPES_KEY, &recipes)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetUpgradeRecipe { recipe })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// remove an upgrade recipe
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `id` - id of the recipe to remove
+fn try_remove_upgrade_recipe<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    id: u32,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let mut recipes: Vec<UpgradeRecipe> = json_load(&deps.storage, RECIPES_KEY)?;
+    let len = recipes.len();
+    recipes.retain(|r| r.id != id);
+    if recipes.len() == len {
+        return Err(StdError::generic_err(format!(
+            "There is no upgrade recipe {}",
+            id
+        )));
+    }
+    json_save(&mut deps.storage, RECIPES_KEY, &recipes)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveUpgradeRecipe { id })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the maximum total pack supply and the number of packs an address can mint
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `max_supply` - optional maximum number of packs that can ever be minted
+/// * `address_limit` - optional maximum number of packs an address can mint
+/// * `max_packs_per_tx` - optional maximum number of packs that can be bought in one transaction
+fn try_set_mint_limits<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    max_supply: Option<u32>,
+    address_limit: Option<u32>,
+    max_packs_per_tx: Option<u32>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let max_packs_per_tx = max_packs_per_tx.unwrap_or(1);
+    if max_packs_per_tx == 0 {
+        return Err(StdError::generic_err(
+            "At least 1 pack must be allowed per transaction",
+        ));
+    }
+    config.max_supply = max_supply;
+    config.address_limit = address_limit;
+    config.max_packs_per_tx = max_packs_per_tx;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMintLimits {
+            max_supply,
+            address_limit,
+            max_packs_per_tx,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the addresses besides the admin that can airdrop heroes
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `minters` - addresses that can airdrop heroes
+fn try_set_minters<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    minters: &[HumanAddr],
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    config.minters = minters
+        .iter()
+        .map(|m| deps.api.canonical_address(m))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMinters {
+            minters: minters.to_vec(),
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the elements that can be assigned to newly minted heroes
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `elements` - the elements new heroes can have
+fn try_set_elements<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    elements: Vec<String>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if elements.len() > 255 {
+        return Err(StdError::generic_err(
+            "There can not be more than 255 elements",
+        ));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    config.elements = Vec::new();
+    for element in elements.into_iter() {
+        if element.is_empty() {
+            return Err(StdError::generic_err("An element name can not be blank"));
+        }
+        if !config.elements.contains(&element) {
+            config.elements.push(element);
+        }
+    }
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetElements {
+            elements: config.elements,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// mint free heroes to a list of recipients
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `drops` - the heroes to mint
+fn try_airdrop<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    drops: Vec<AirdropEntry>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if sender_raw != admin && !config.minters.contains(&sender_raw) {
+        return Err(StdError::generic_err(
+            "Only the admin and authorized minters can airdrop heroes",
+        ));
+    }
+    if config.minting_halt {
+        return Err(StdError::generic_err(
+            "The minter has been stopped.  No new cards can be minted",
+        ));
+    }
+    let mut tiers: Vec<StoredPackTier> = json_load(&deps.storage, TIERS_KEY)?;
+    let mut mints: Vec<Mint> = Vec::new();
+    for entry in drops.into_iter() {
+        if config.max_supply.map_or(false, |m| config.mint_cnt >= m) {
+            return Err(StdError::generic_err("All packs have been minted"));
+        }
+        let names = if let Some(names) = entry.names {
+            names
+        } else {
+            let heroes = entry.heroes.unwrap_or(3);
+            if heroes == 0 || heroes > 3 {
+                return Err(StdError::generic_err(
+                    "An airdrop entry must mint from 1 to 3 heroes",
+                ));
+            }
+            // the pack count makes default names unique
+            (1..=heroes)
+                .map(|i| format!("Hero {}-{}", config.mint_cnt, i))
+                .collect()
+        };
+        if names.len() > 3 {
+            return Err(StdError::generic_err(
+                "An airdrop entry must mint from 1 to 3 heroes",
+            ));
+        }
+        let tier_idx = find_tier(&tiers, entry.tier.as_deref())?;
+        let price = StoredPayment {
+            amount: Uint128(0),
+            denom: None,
+            token: None,
+        };
+        mints.extend(mint_pack(
+            deps,
+            &env,
+            &mut config,
+            &mut tiers[tier_idx],
+            names,
+            &entry.recipient,
+            price,
+        )?);
+    }
+    json_save(&mut deps.storage, TIERS_KEY, &tiers)?;
+    let heroes = mints.len() as u32;
+    let messages = if mints.is_empty() {
+        vec![]
+    } else {
+        vec![mint_msg(&deps.api, &config, mints)?]
+    };
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Airdrop { heroes })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// register promo codes for free packs
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `codes` - the codes to register
+fn try_add_promo_codes<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    codes: Vec<PromoCode>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let tiers: Vec<StoredPackTier> = json_load(&deps.storage, TIERS_KEY)?;
+    let count = codes.len() as u32;
+    for code in codes.into_iter() {
+        if code.hash.len() != 32 {
+            return Err(StdError::generic_err(
+                "Promo codes must be registered as 32-byte sha256 hashes",
+            ));
+        }
+        if code.uses == 0 {
+            return Err(StdError::generic_err(
+                "A promo code must be redeemable at least once",
+            ));
+        }
+        find_tier(&tiers, code.tier.as_deref())?;
+        let mut id_store = PrefixedStorage::new(PREFIX_PROMO_IDS, &mut deps.storage);
+        if may_load::<u32, _>(&id_store, code.hash.as_slice())?.is_some() {
+            return Err(StdError::generic_err(format!(
+                "Promo code hash {} is already registered",
+                code.hash
+            )));
+        }
+        save(&mut id_store, code.hash.as_slice(), &config.promo_cnt)?;
+        let mut code_store = PrefixedStorage::new(PREFIX_PROMO_CODES, &mut deps.storage);
+        save(
+            &mut code_store,
+            &config.promo_cnt.to_le_bytes(),
+            &StoredPromoCode {
+                hash: code.hash.0,
+                tier: code.tier,
+                uses: code.uses,
+                redeemed: 0,
+                expires: code.expires,
+            },
+        )?;
+        config.promo_cnt += 1;
+    }
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AddPromoCodes { count })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// redeem a promo code for a free pack
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `code` - the promo code
+/// * `names` - list of names for the newly minted cards
+fn try_redeem_code<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    code: &str,
+    names: Vec<String>,
+) -> HandleResult {
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if config.minting_halt {
+        return Err(StdError::generic_err(
+            "The minter has been stopped.  No new cards can be minted",
+        ));
+    }
+    if config.max_supply.map_or(false, |m| config.mint_cnt >= m) {
+        return Err(StdError::generic_err("All packs have been minted"));
+    }
+    if names.len() < 3 {
+        return Err(StdError::generic_err(
+            "You must supply at least 3 names to mint 3 cards",
+        ));
+    }
+    let hash = sha_256(code.as_bytes());
+    let id_store = ReadonlyPrefixedStorage::new(PREFIX_PROMO_IDS, &deps.storage);
+    let id: u32 =
+        may_load(&id_store, &hash)?.ok_or_else(|| StdError::generic_err("Invalid promo code"))?;
+    let mut code_store = PrefixedStorage::new(PREFIX_PROMO_CODES, &mut deps.storage);
+    let mut promo: StoredPromoCode = load(&code_store, &id.to_le_bytes())?;
+    if promo.expires.map_or(false, |e| env.block.time >= e) {
+        return Err(StdError::generic_err("This promo code has expired"));
+    }
+    if promo.redeemed >= promo.uses {
+        return Err(StdError::generic_err("This promo code has been used up"));
+    }
+    promo.redeemed += 1;
+    save(&mut code_store, &id.to_le_bytes(), &promo)?;
+    let redeemer = deps.api.canonical_address(&env.message.sender)?;
+    let mut redeemer_store = PrefixedStorage::multilevel(
+        &[PREFIX_PROMO_REDEEMERS, &id.to_le_bytes()],
+        &mut deps.storage,
+    );
+    if redeemer_store.get(redeemer.as_slice()).is_some() {
+        return Err(StdError::generic_err(
+            "This address has already redeemed this promo code",
+        ));
+    }
+    redeemer_store.set(redeemer.as_slice(), &[1]);
+    let mut tiers: Vec<StoredPackTier> = json_load(&deps.storage, TIERS_KEY)?;
+    let tier_idx = find_tier(&tiers, promo.tier.as_deref())?;
+    let price = StoredPayment {
+        amount: Uint128(0),
+        denom: None,
+        token: None,
+    };
+    let mints = mint_pack(
+        deps,
+        &env,
+        &mut config,
+        &mut tiers[tier_idx],
+        names,
+        &env.message.sender,
+        price,
+    )?;
+    json_save(&mut deps.storage, TIERS_KEY, &tiers)?;
+
+    Ok(HandleResponse {
+        messages: vec![mint_msg(&deps.api, &config, mints)?],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RedeemCode { status: Success })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the rules hero names must follow
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `rules` - the name rules
+fn try_set_name_rules<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    rules: NameRules,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if rules.min_length == 0 || rules.min_length > rules.max_length {
+        return Err(StdError::generic_err(
+            "Name lengths must satisfy 1 <= min_length <= max_length",
+        ));
+    }
+    save(&mut deps.storage, NAME_RULES_KEY, &rules)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetNameRules { rules })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// add or remove substrings that can not appear in hero names
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `substrings` - the substrings to update
+/// * `ban` - true if the substrings should be banned
+fn try_update_banned<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    substrings: &[String],
+    ban: bool,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let mut banned: Vec<String> = may_load(&deps.storage, BANNED_KEY)?.unwrap_or_default();
+    // banned substrings are compared against normalized names
+    for normalized in substrings.iter().map(|s| normalize_name(s)) {
+        if normalized.is_empty() {
+            continue;
+        }
+        if ban {
+            if !banned.contains(&normalized) {
+                banned.push(normalized);
+            }
+        } else {
+            banned.retain(|b| *b != normalized);
+        }
+    }
+    save(&mut deps.storage, BANNED_KEY, &banned)?;
+    let data = if ban {
+        HandleAnswer::AddBannedSubstrings { status: Success }
+    } else {
+        HandleAnswer::RemoveBannedSubstrings { status: Success }
+    };
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&data)?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// pay the rename fee to give a hero a new name
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `hero` - the hero to rename
+/// * `name` - the hero's new name
+fn try_rename_hero<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    hero: HeroInfo,
+    name: String,
+) -> HandleResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let rules: NameRules = load(&deps.storage, NAME_RULES_KEY)?;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    if rules.rename_fee.u128() > 0 {
+        let required = Coin {
+            denom: "uscrt".to_string(),
+            amount: rules.rename_fee,
+        };
+        if env.message.sent_funds != [required.clone()] {
+            return Err(StdError::generic_err(format!(
+                "You must pay exactly {}uscrt to rename a hero",
+                rules.rename_fee
+            )));
+        }
+        messages.extend(pay_revenue(
+            &deps.api,
+            &env,
+            &config,
+            required,
+            "rename fee",
+        )?);
+    } else if !env.message.sent_funds.is_empty() {
+        return Err(StdError::generic_err("Renaming a hero is free"));
+    }
+    let stored_ci = find_card_version(&deps.api, &config, &hero.contract_address, "rename")?;
+    let whitelist_err = format!(
+        "This contract: {} has not been fully whitelisted on NFT contract:",
+        &env.contract.address
+    );
+    let viewing_key: String = load(&deps.storage, VKEY_KEY)?;
+    let viewer = ViewerInfo {
+        address: env.contract.address.clone(),
+        viewing_key,
+    };
+    let (_stats, mut priv_meta) = get_stats(
+        &deps.querier,
+        hero.token_id.clone(),
+        viewer,
+        stored_ci,
+        hero.contract_address.clone(),
+        &env.message.sender,
+        &whitelist_err,
+        "rename",
+    )?;
+    let hero_key = name_key(&stored_ci.address, &hero.token_id);
+    // release the old name before registering the new one
+    if let Some(old) = priv_meta.name.as_ref() {
+        transfer_name(&mut deps.storage, old, &hero_key, None);
+    }
+    check_name(&mut deps.storage, &name, &hero_key)?;
+    priv_meta.name = Some(name.clone());
+    messages.push(set_public_metadata_msg(
+        hero.token_id.clone(),
+        Metadata {
+            name: Some(name),
+            description: None,
+            image: None,
+        },
+        None,
+        BLOCK_SIZE,
+        stored_ci.code_hash.clone(),
+        hero.contract_address.clone(),
+    )?);
+    messages.push(set_private_metadata_msg(
+        hero.token_id,
+        priv_meta,
+        None,
+        BLOCK_SIZE,
+        stored_ci.code_hash.clone(),
+        hero.contract_address,
+    )?);
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RenameHero { status: Success })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the timed sale phases
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `phases` - the sale phases
+fn try_set_sale_phases<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    phases: Vec<SalePhase>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    for (i, phase) in phases.iter().enumerate() {
+        if phase.name.is_empty() {
+            return Err(StdError::generic_err("A sale phase must have a name"));
+        }
+        if phases.iter().skip(i + 1).any(|p| p.name == phase.name) {
+            return Err(StdError::generic_err(format!(
+                "Sale phase {} is listed more than once",
+                phase.name
+            )));
+        }
+        if phase.price_bps == 0 {
+            return Err(StdError::generic_err(format!(
+                "The {} phase's price can not be zero",
+                phase.name
+            )));
+        }
+        if phase.end.map_or(false, |e| e <= phase.start) {
+            return Err(StdError::generic_err(format!(
+                "The {} phase must end after it starts",
+                phase.name
+            )));
+        }
+    }
+    json_save(&mut deps.storage, PHASES_KEY, &phases)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetSalePhases { phases })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// add addresses to or remove addresses from a sale phase's allowlist
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `phase` - name of the sale phase
+/// * `addresses` - list of addresses to update
+/// * `add` - true if the addresses should be added to the allowlist
+fn try_update_allowlist<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    phase: String,
+    addresses: &[HumanAddr],
+    add: bool,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let phases: Vec<SalePhase> = json_may_load(&deps.storage, PHASES_KEY)?.unwrap_or_default();
+    if add && !phases.iter().any(|p| p.name == phase) {
+        return Err(StdError::generic_err(format!(
+            "There is no sale phase named {}",
+            phase
+        )));
+    }
+    let raws = addresses
+        .iter()
+        .map(|a| deps.api.canonical_address(a))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    let mut allow_store =
+        PrefixedStorage::multilevel(&[PREFIX_ALLOWLIST, phase.as_bytes()], &mut deps.storage);
+    for raw in raws.iter() {
+        if add {
+            allow_store.set(raw.as_slice(), &[1]);
+        } else {
+            allow_store.remove(raw.as_slice());
+        }
+    }
+    let count = raws.len() as u32;
+    let answer = if add {
+        HandleAnswer::AddToAllowlist {
+            phase,
+            added: count,
+        }
+    } else {
+        HandleAnswer::RemoveFromAllowlist {
+            phase,
+            removed: count,
+        }
+    };
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&answer)?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// register a referral code
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `code` - the referral code
+fn try_register_referral_code<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    code: String,
+) -> HandleResult {
+    if code.len() < 3
+        || code.len() > 20
+        || !code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(StdError::generic_err(
+            "A referral code must be 3 to 20 letters, digits, dashes or underscores",
+        ));
+    }
+    let sender_raw = deps.api.canonical_address(sender)?;
+    let ref_store = ReadonlyPrefixedStorage::new(PREFIX_REFERRERS, &deps.storage);
+    let may_referrer: Option<StoredReferrer> = may_load(&ref_store, sender_raw.as_slice())?;
+    let mut code_store = PrefixedStorage::new(PREFIX_REFERRAL_CODES, &mut deps.storage);
+    if code_store.get(code.as_bytes()).is_some() {
+        return Err(StdError::generic_err(format!(
+            "Referral code {} is already taken",
+            code
+        )));
+    }
+    save(&mut code_store, code.as_bytes(), &sender_raw)?;
+    let referrer = if let Some(mut referrer) = may_referrer {
+        // release the old code
+        code_store.remove(referrer.code.as_bytes());
+        referrer.code = code.clone();
+        referrer
+    } else {
+        StoredReferrer {
+            code: code.clone(),
+            packs: 0,
+            rewards: Vec::new(),
+        }
+    };
+    let mut ref_store = PrefixedStorage::new(PREFIX_REFERRERS, &mut deps.storage);
+    save(&mut ref_store, sender_raw.as_slice(), &referrer)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RegisterReferralCode { code })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// claim the referral rewards that have accrued to the sender
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+fn try_claim_referral_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let ref_store = ReadonlyPrefixedStorage::new(PREFIX_REFERRERS, &deps.storage);
+    let mut referrer: StoredReferrer = may_load(&ref_store, sender_raw.as_slice())?
+        .ok_or_else(|| StdError::generic_err("You have not registered a referral code"))?;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let mut coins: Vec<Coin> = Vec::new();
+    let mut claimed: Vec<ReferralReward> = Vec::new();
+    for reward in referrer.rewards.iter_mut() {
+        if reward.unclaimed == Uint128(0) {
+            continue;
+        }
+        claimed.push(reward.get_humanized(&deps.api)?);
+        if let Some(denom) = reward.denom.as_ref() {
+            coins.push(Coin {
+                denom: denom.clone(),
+                amount: reward.unclaimed,
+            });
+        } else if let Some(token) = reward.token.as_ref() {
+            messages.push(transfer_msg(
+                env.message.sender.clone(),
+                reward.unclaimed,
+                None,
+                BLOCK_SIZE,
+                token.code_hash.clone(),
+                deps.api.human_address(&token.address)?,
+            )?);
+        }
+        reward.unclaimed = Uint128(0);
+    }
+    if claimed.is_empty() {
+        return Err(StdError::generic_err(
+            "You do not have any referral rewards to claim",
+        ));
+    }
+    let mut ref_store = PrefixedStorage::new(PREFIX_REFERRERS, &mut deps.storage);
+    save(&mut ref_store, sender_raw.as_slice(), &referrer)?;
+    if !coins.is_empty() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: env.message.sender,
+            amount: coins,
+        }));
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClaimReferralRewards { claimed })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the referrer's share of the pack price
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `share` - referrer's share of the pack price in basis points
+/// * `pay_immediately` - true if referrers should be paid with each sale
+fn try_set_referral_terms<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    share: u16,
+    pay_immediately: bool,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if share > TOTAL_SHARES {
+        return Err(StdError::generic_err(format!(
+            "The referral share can not exceed {} basis points",
+            TOTAL_SHARES
+        )));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    config.referral_share = share;
+    config.pay_referrals_immediately = pay_immediately;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetReferralTerms {
+            share,
+            pay_immediately,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set a discount off the pack price
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `discount` - discount in basis points
+/// * `ends` - optional time after which the discount ends
+fn try_set_discount<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    discount: u16,
+    ends: Option<u64>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    if discount >= TOTAL_SHARES {
+        return Err(StdError::generic_err(
+            "The discount must be less than 10000 basis points",
+        ));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    config.discount = discount;
+    config.discount_ends = ends;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetDiscount { discount, ends })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// set the recipients of pack sale revenue
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `sender` - a reference to the message sender
+/// * `recipients` - recipients and their shares in basis points
+fn try_set_revenue_split<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sender: &HumanAddr,
+    recipients: Vec<RevenueShare>,
+) -> HandleResult {
+    let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
+    let sender_raw = deps.api.canonical_address(sender)?;
+    if sender_raw != admin {
+        return Err(StdError::generic_err(
+            "This is an admin command. Admin commands can only be run from admin address",
+        ));
+    }
+    let total: u32 = recipients.iter().map(|r| r.share as u32).sum();
+    if total != TOTAL_SHARES as u32 {
+        return Err(StdError::generic_err(format!(
+            "Revenue shares must total {} basis points",
+            TOTAL_SHARES
+        )));
+    }
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    config.revenue_split = recipients
+        .iter()
+        .map(|r| {
+            Ok(StoreRevenueShare {
+                address: deps.api.canonical_address(&r.address)?,
+                share: r.share,
+            })
+        })
+        .collect::<StdResult<Vec<StoreRevenueShare>>>()?;
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::Mint { status: Success })?),
+        data: Some(to_binary(&HandleAnswer::SetRevenueSplit { recipients })?),
     })
 }
 
@@ -593,10 +3227,12 @@ fn try_new_card_contract<S: Storage, A: Api, Q: Querier>(
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
 /// * `sender` - a reference to the message sender
 /// * `address` - the new multi sig address
+/// * `code_hash` - optional code hash of the new multi sig
 fn try_new_multi_sig<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     sender: &HumanAddr,
     address: HumanAddr,
+    code_hash: Option<String>,
 ) -> HandleResult {
     let admin: CanonicalAddr = load(&deps.storage, ADMIN_KEY)?;
     let sender_raw = deps.api.canonical_address(sender)?;
@@ -608,8 +3244,15 @@ fn try_new_multi_sig<S: Storage, A: Api, Q: Querier>(
 
     let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
     let new_address_raw = deps.api.canonical_address(&address)?;
-    if config.multi_sig != new_address_raw {
+    if config.multi_sig != new_address_raw || config.multi_sig_code_hash != code_hash {
+        // revenue that went to the old multi sig now goes to the new one
+        for recipient in config.revenue_split.iter_mut() {
+            if recipient.address == config.multi_sig {
+                recipient.address = new_address_raw.clone();
+            }
+        }
         config.multi_sig = new_address_raw;
+        config.multi_sig_code_hash = code_hash;
         save(&mut deps.storage, CONFIG_KEY, &config)?;
     }
 
@@ -684,10 +3327,332 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
     let response = match msg {
         QueryMsg::Config {} => query_config(deps),
         QueryMsg::PacksMinted {} => query_packs_minted(&deps.storage),
+        QueryMsg::PackTiers {} => query_pack_tiers(&deps.storage),
+        QueryMsg::UpgradeRecipes {} => query_upgrade_recipes(&deps.storage),
+        QueryMsg::NameRules {} => query_name_rules(&deps.storage),
+        QueryMsg::SalePhases {} => query_sale_phases(&deps.storage),
+        QueryMsg::MintAllowance {
+            address,
+            viewing_key,
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_mint_allowance(deps, &a)),
+        QueryMsg::ReferralStats {
+            address,
+            viewing_key,
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_referral_stats(deps, &a)),
+        QueryMsg::Lineage { hero, depth } => query_lineage(deps, hero, depth),
+        QueryMsg::MigratedHero { hero } => query_migrated_hero(deps, &hero),
+        QueryMsg::MintHistory {
+            address,
+            viewing_key,
+            page,
+            page_size,
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_mint_history(deps, &a, page, page_size)),
+        QueryMsg::UpgradeHistory {
+            address,
+            viewing_key,
+            page,
+            page_size,
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_upgrade_history(deps, &a, page, page_size)),
+        QueryMsg::DumpMintHistory {
+            admin,
+            viewing_key,
+            start_from,
+            limit,
+        } => check_viewer(deps, &admin, viewing_key)
+            .and_then(|a| query_dump_mint_history(deps, &a, start_from, limit)),
+        QueryMsg::DumpUpgradeHistory {
+            admin,
+            viewing_key,
+            start_from,
+            limit,
+        } => check_viewer(deps, &admin, viewing_key)
+            .and_then(|a| query_dump_upgrade_history(deps, &a, start_from, limit)),
+        QueryMsg::PromoCodes {
+            admin,
+            viewing_key,
+            start_from,
+            limit,
+        } => check_viewer(deps, &admin, viewing_key)
+            .and_then(|a| query_promo_codes(deps, &a, start_from, limit)),
+        QueryMsg::PreviewUpgrade {
+            address,
+            viewing_key,
+            burn,
+            upgrade,
+            recipe,
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_preview_upgrade(deps, &a, burn, upgrade, recipe)),
+        QueryMsg::WithPermit { permit, query } => permit_queries(deps, &permit, query),
     };
     pad_query_result(response, BLOCK_SIZE)
 }
 
+/// Returns QueryResult from a query authenticated by a permit
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `permit` - a reference to the querier's Permit
+/// * `query` - the QueryWithPermit to perform
+fn permit_queries<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    permit: &Permit,
+    query: QueryWithPermit,
+) -> QueryResult {
+    let my_address_raw: CanonicalAddr = load(&deps.storage, MY_ADDRESS_KEY)?;
+    let my_address = deps.api.human_address(&my_address_raw)?;
+    let permission = match query {
+        QueryWithPermit::MintAllowance {}
+        | QueryWithPermit::MintHistory { .. }
+        | QueryWithPermit::UpgradeHistory { .. }
+        | QueryWithPermit::PreviewUpgrade { .. } => Permission::History,
+        QueryWithPermit::ReferralStats {} => Permission::Referrals,
+        _ => Permission::Owner,
+    };
+    let address_raw = validate_permit(&deps.storage, permit, &my_address, permission)?;
+    match query {
+        QueryWithPermit::MintAllowance {} => query_mint_allowance(deps, &address_raw),
+        QueryWithPermit::ReferralStats {} => query_referral_stats(deps, &address_raw),
+        QueryWithPermit::MintHistory { page, page_size } => {
+            query_mint_history(deps, &address_raw, page, page_size)
+        }
+        QueryWithPermit::UpgradeHistory { page, page_size } => {
+            query_upgrade_history(deps, &address_raw, page, page_size)
+        }
+        QueryWithPermit::DumpMintHistory { start_from, limit } => {
+            query_dump_mint_history(deps, &address_raw, start_from, limit)
+        }
+        QueryWithPermit::DumpUpgradeHistory { start_from, limit } => {
+            query_dump_upgrade_history(deps, &address_raw, start_from, limit)
+        }
+        QueryWithPermit::PromoCodes { start_from, limit } => {
+            query_promo_codes(deps, &address_raw, start_from, limit)
+        }
+        QueryWithPermit::PreviewUpgrade {
+            burn,
+            upgrade,
+            recipe,
+        } => query_preview_upgrade(deps, &address_raw, burn, upgrade, recipe),
+    }
+}
+
+/// Returns QueryResult displaying the packs an address minted
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the authenticated querier's address
+/// * `page` - optional page to display
+/// * `page_size` - optional number of mints per page
+fn query_mint_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &CanonicalAddr,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> QueryResult {
+    let records: Vec<(u64, StoredMintRecord)> = get_records(
+        &deps.storage,
+        PREFIX_MINT_HISTORY,
+        PREFIX_MINT_IDS,
+        address,
+        page.unwrap_or(0),
+        page_size.unwrap_or(30),
+    )?;
+    to_binary(&QueryAnswer::MintHistory {
+        history: records
+            .into_iter()
+            .map(|(id, r)| r.into_humanized(&deps.api, id))
+            .collect::<StdResult<Vec<MintRecord>>>()?,
+    })
+}
+
+/// Returns QueryResult displaying the upgrades an address did
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the authenticated querier's address
+/// * `page` - optional page to display
+/// * `page_size` - optional number of upgrades per page
+fn query_upgrade_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &CanonicalAddr,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> QueryResult {
+    let records: Vec<(u64, StoredUpgradeRecord)> = get_records(
+        &deps.storage,
+        PREFIX_UPGRADE_HISTORY,
+        PREFIX_UPGRADE_IDS,
+        address,
+        page.unwrap_or(0),
+        page_size.unwrap_or(30),
+    )?;
+    to_binary(&QueryAnswer::UpgradeHistory {
+        history: records
+            .into_iter()
+            .map(|(id, r)| r.into_humanized(&deps.api, id))
+            .collect::<StdResult<Vec<UpgradeRecord>>>()?,
+    })
+}
+
+/// Returns QueryResult dumping every address' mints
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `admin` - a reference to the authenticated querier's address
+/// * `start_from` - optional index of the mint to start display from
+/// * `limit` - optional number of mints to display
+fn query_dump_mint_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    admin: &CanonicalAddr,
+    start_from: Option<u64>,
+    limit: Option<u64>,
+) -> QueryResult {
+    check_admin(&deps.storage, admin)?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let start = start_from.unwrap_or(0);
+    let end = (start + limit.unwrap_or(256)).min(config.mint_record_cnt);
+    let his_store = ReadonlyPrefixedStorage::new(PREFIX_MINT_HISTORY, &deps.storage);
+    let mut history: Vec<MintRecord> = Vec::new();
+    for id in start..end {
+        let may_rec: Option<StoredMintRecord> = may_load(&his_store, &id.to_le_bytes())?;
+        if let Some(record) = may_rec {
+            history.push(record.into_humanized(&deps.api, id)?);
+        }
+    }
+    to_binary(&QueryAnswer::MintHistory { history })
+}
+
+/// Returns QueryResult displaying the promo codes and how often they were redeemed
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `admin` - a reference to the authenticated querier's address
+/// * `start_from` - optional index of the code to start display from
+/// * `limit` - optional number of codes to display
+fn query_promo_codes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    admin: &CanonicalAddr,
+    start_from: Option<u32>,
+    limit: Option<u32>,
+) -> QueryResult {
+    check_admin(&deps.storage, admin)?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let start = start_from.unwrap_or(0);
+    let end = start
+        .saturating_add(limit.unwrap_or(256))
+        .min(config.promo_cnt);
+    let code_store = ReadonlyPrefixedStorage::new(PREFIX_PROMO_CODES, &deps.storage);
+    let mut codes: Vec<PromoCodeInfo> = Vec::new();
+    for id in start..end {
+        let may_code: Option<StoredPromoCode> = may_load(&code_store, &id.to_le_bytes())?;
+        if let Some(code) = may_code {
+            codes.push(PromoCodeInfo {
+                id,
+                hash: Binary(code.hash),
+                tier: code.tier,
+                uses: code.uses,
+                redeemed: code.redeemed,
+                expires: code.expires,
+            });
+        }
+    }
+    to_binary(&QueryAnswer::PromoCodes { codes })
+}
+
+/// Returns QueryResult dumping every address' upgrades
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `admin` - a reference to the authenticated querier's address
+/// * `start_from` - optional index of the upgrade to start display from
+/// * `limit` - optional number of upgrades to display
+fn query_dump_upgrade_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    admin: &CanonicalAddr,
+    start_from: Option<u64>,
+    limit: Option<u64>,
+) -> QueryResult {
+    check_admin(&deps.storage, admin)?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let start = start_from.unwrap_or(0);
+    let end = (start + limit.unwrap_or(256)).min(config.upgrade_record_cnt);
+    let his_store = ReadonlyPrefixedStorage::new(PREFIX_UPGRADE_HISTORY, &deps.storage);
+    let mut history: Vec<UpgradeRecord> = Vec::new();
+    for id in start..end {
+        let may_rec: Option<StoredUpgradeRecord> = may_load(&his_store, &id.to_le_bytes())?;
+        if let Some(record) = may_rec {
+            history.push(record.into_humanized(&deps.api, id)?);
+        }
+    }
+    to_binary(&QueryAnswer::UpgradeHistory { history })
+}
+
+/// Returns StdResult<CanonicalAddr> of the querier after validating their viewing key
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the querier's address
+/// * `viewing_key` - String key used for authentication
+fn check_viewer<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    viewing_key: String,
+) -> StdResult<CanonicalAddr> {
+    let address_raw = deps.api.canonical_address(address)?;
+    check_key(&deps.storage, &address_raw, viewing_key)?;
+    Ok(address_raw)
+}
+
+/// Returns StdResult<()> result of verifying the querier is the admin
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `address` - a reference to the authenticated querier's address
+fn check_admin<S: ReadonlyStorage>(storage: &S, address: &CanonicalAddr) -> StdResult<()> {
+    let real_admin: CanonicalAddr = load(storage, ADMIN_KEY)?;
+    if real_admin != *address {
+        return Err(StdError::generic_err(
+            "This is an admin query. Admin queries can only be run from admin address",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns StdResult<()> result of validating an address' viewing key
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `address` - a reference to the address whose key should be validated
+/// * `viewing_key` - String key used for authentication
+fn check_key<S: ReadonlyStorage>(
+    storage: &S,
+    address: &CanonicalAddr,
+    viewing_key: String,
+) -> StdResult<()> {
+    // load the address' key
+    let read_key = ReadonlyPrefixedStorage::new(PREFIX_VIEW_KEY, storage);
+    let load_key: [u8; VIEWING_KEY_SIZE] =
+        may_load(&read_key, address.as_slice())?.unwrap_or_else(|| [0u8; VIEWING_KEY_SIZE]);
+    let input_key = ViewingKey(viewing_key);
+    // if key matches
+    if input_key.check_viewing_key(&load_key) {
+        return Ok(());
+    }
+    Err(StdError::generic_err(
+        "Wrong viewing key for this address or viewing key not set",
+    ))
+}
+
 /// Returns QueryResult displaying the number of packs minted
 ///
 /// # Arguments
@@ -701,6 +3666,336 @@ fn query_packs_minted<S: ReadonlyStorage>(storage: &S) -> QueryResult {
     })
 }
 
+/// Returns QueryResult displaying the pack tiers and their remaining supply
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+fn query_pack_tiers<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let tiers: Vec<StoredPackTier> = json_load(storage, TIERS_KEY)?;
+
+    to_binary(&QueryAnswer::PackTiers {
+        tiers: tiers
+            .into_iter()
+            .map(|t| PackTierInfo {
+                remaining: t.tier.supply.map(|s| s.saturating_sub(t.minted)),
+                tier: t.tier,
+                minted: t.minted,
+            })
+            .collect(),
+    })
+}
+
+/// Returns QueryResult displaying the upgrade recipes
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+fn query_upgrade_recipes<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let recipes: Vec<UpgradeRecipe> = json_load(storage, RECIPES_KEY)?;
+
+    to_binary(&QueryAnswer::UpgradeRecipes { recipes })
+}
+
+/// Returns QueryResult displaying the possible outcomes of an upgrade
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the authenticated querier's address
+/// * `burn` - list of heroes to burn
+/// * `upgrade` - the hero to upgrade
+/// * `recipe` - optional id of the upgrade recipe to use
+fn query_preview_upgrade<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &CanonicalAddr,
+    burn: Vec<HeroInfo>,
+    upgrade: HeroInfo,
+    recipe: Option<u32>,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let recipes: Vec<UpgradeRecipe> = json_load(&deps.storage, RECIPES_KEY)?;
+    let recipe = find_recipe(&recipes, recipe)?;
+    if burn.len() != recipe.burns as usize {
+        return Err(StdError::generic_err(format!(
+            "Upgrade recipe {} requires burning exactly {} heroes",
+            recipe.id, recipe.burns
+        )));
+    }
+    let owner = deps.api.human_address(address)?;
+    let my_address_raw: CanonicalAddr = load(&deps.storage, MY_ADDRESS_KEY)?;
+    let my_address = deps.api.human_address(&my_address_raw)?;
+    let whitelist_err = format!(
+        "This contract: {} has not been fully whitelisted on NFT contract:",
+        &my_address
+    );
+    let viewing_key: String = load(&deps.storage, VKEY_KEY)?;
+    let viewer = ViewerInfo {
+        address: my_address,
+        viewing_key,
+    };
+    let mut burn_points = 0i16;
+    for hero in burn.into_iter() {
+        let stored_ci = find_card_version(&deps.api, &config, &hero.contract_address, "burn")?;
+        let (stats, _m) = get_stats(
+            &deps.querier,
+            hero.token_id,
+            viewer.clone(),
+            stored_ci,
+            hero.contract_address,
+            &owner,
+            &whitelist_err,
+            "burn",
+        )?;
+        burn_points += stats.current.iter().map(|u| *u as i16).sum::<i16>();
+    }
+    let stored_ci = find_card_version(&deps.api, &config, &upgrade.contract_address, "upgrade")?;
+    let (stats, _m) = get_stats(
+        &deps.querier,
+        upgrade.token_id,
+        viewer,
+        stored_ci,
+        upgrade.contract_address,
+        &owner,
+        &whitelist_err,
+        "upgrade",
+    )?;
+    let pre_sum = stats.current.iter().map(|u| *u as i16).sum::<i16>();
+    let power_diff = recipe.burns as i16 * pre_sum - burn_points;
+    let base_upgrade = get_base_upgrade(recipe, power_diff);
+    let cap = stats.skill_cap();
+    // every adjustment is equally likely, so count how many lead to each skill value
+    let skills = stats
+        .current
+        .iter()
+        .enumerate()
+        .map(|(i, u)| {
+            let mut outcomes: Vec<SkillOutcome> = Vec::new();
+            for adjust in recipe.adjust.iter() {
+                let value = upgrade_skill(recipe, base_upgrade, i, *u, *adjust, cap);
+                if let Some(outcome) = outcomes.iter_mut().find(|o| o.value == value) {
+                    outcome.chances += 1;
+                } else {
+                    outcomes.push(SkillOutcome { value, chances: 1 });
+                }
+            }
+            outcomes.sort_by_key(|o| o.value);
+            SkillPreview {
+                current: *u,
+                outcomes,
+            }
+        })
+        .collect();
+
+    to_binary(&QueryAnswer::PreviewUpgrade {
+        recipe: recipe.id,
+        power_diff,
+        base_upgrade,
+        out_of: recipe.adjust.len() as u32,
+        skills,
+    })
+}
+
+/// Returns StdResult<&StoreContractInfo> of the card contract a hero belongs to
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `config` - a reference to the Config
+/// * `address` - a reference to the hero's contract address
+/// * `action` - either "burn" or "upgrade" depending on what is being done to the hero
+fn find_card_version<'a, A: Api>(
+    api: &A,
+    config: &'a Config,
+    address: &HumanAddr,
+    action: &str,
+) -> StdResult<&'a StoreContractInfo> {
+    let raw = api.canonical_address(address)?;
+    config
+        .card_versions
+        .iter()
+        .find(|v| v.address == raw)
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Can not {} heroes from an unknown guild (Unknown NFT contract address: {})",
+                action, address
+            ))
+        })
+}
+
+/// Returns QueryResult displaying the token a legacy hero was migrated to
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `hero` - a reference to the legacy hero
+fn query_migrated_hero<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    hero: &HeroInfo,
+) -> QueryResult {
+    let contract = deps.api.canonical_address(&hero.contract_address)?;
+
+    to_binary(&QueryAnswer::MigratedHero {
+        migrated_to: may_load_migration(&deps.storage, &contract, &hero.token_id)?,
+    })
+}
+
+/// Returns QueryResult displaying a hero's ancestors
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `hero` - the hero whose ancestors should be displayed
+/// * `depth` - optional number of generations to display
+fn query_lineage<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    hero: HeroInfo,
+    depth: Option<u32>,
+) -> QueryResult {
+    let depth = depth.unwrap_or(5).min(10);
+
+    to_binary(&QueryAnswer::Lineage {
+        lineage: get_lineage_node(deps, hero, depth)?,
+    })
+}
+
+/// Returns StdResult<LineageNode> of a hero and its ancestors
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `hero` - the hero whose ancestors should be displayed
+/// * `depth` - number of generations of ancestors still to display
+fn get_lineage_node<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    hero: HeroInfo,
+    depth: u32,
+) -> StdResult<LineageNode> {
+    let contract = deps.api.canonical_address(&hero.contract_address)?;
+    let (generation, parents) =
+        if let Some(lineage) = may_load_lineage(&deps.storage, &contract, &hero.token_id)? {
+            let parents = if depth > 0 {
+                lineage
+                    .parents
+                    .into_iter()
+                    .map(|p| get_lineage_node(deps, p, depth - 1))
+                    .collect::<StdResult<Vec<LineageNode>>>()?
+            } else {
+                Vec::new()
+            };
+            (lineage.generation, parents)
+        } else {
+            (0, Vec::new())
+        };
+    Ok(LineageNode {
+        hero,
+        generation,
+        parents,
+    })
+}
+
+/// Returns QueryResult displaying the rules hero names must follow
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+fn query_name_rules<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let rules: NameRules = load(storage, NAME_RULES_KEY)?;
+
+    to_binary(&QueryAnswer::NameRules { rules })
+}
+
+/// Returns QueryResult displaying the sale phases
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+fn query_sale_phases<S: ReadonlyStorage>(storage: &S) -> QueryResult {
+    let phases: Vec<SalePhase> = json_may_load(storage, PHASES_KEY)?.unwrap_or_default();
+
+    to_binary(&QueryAnswer::SalePhases { phases })
+}
+
+/// Returns QueryResult displaying the number of packs an address has minted and can still mint
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address_raw` - a reference to the authenticated querier's address
+fn query_mint_allowance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address_raw: &CanonicalAddr,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let cnt_store = ReadonlyPrefixedStorage::new(PREFIX_MINT_COUNTS, &deps.storage);
+    let minted: u32 = may_load(&cnt_store, address_raw.as_slice())?.unwrap_or(0);
+    let phases: Vec<SalePhase> = json_may_load(&deps.storage, PHASES_KEY)?.unwrap_or_default();
+    let phases = phases
+        .into_iter()
+        .map(|p| {
+            let phase_store = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_PHASE_MINTS, p.name.as_bytes()],
+                &deps.storage,
+            );
+            let phase_minted: u32 = may_load(&phase_store, address_raw.as_slice())?.unwrap_or(0);
+            let allowlisted = if p.access == PhaseAccess::Allowlist {
+                let allow_store = ReadonlyPrefixedStorage::multilevel(
+                    &[PREFIX_ALLOWLIST, p.name.as_bytes()],
+                    &deps.storage,
+                );
+                Some(allow_store.get(address_raw.as_slice()).is_some())
+            } else {
+                None
+            };
+            Ok(PhaseAllowance {
+                name: p.name,
+                allowlisted,
+                minted: phase_minted,
+                remaining: p.address_limit.map(|l| l.saturating_sub(phase_minted)),
+            })
+        })
+        .collect::<StdResult<Vec<PhaseAllowance>>>()?;
+
+    to_binary(&QueryAnswer::MintAllowance {
+        minted,
+        remaining: config.address_limit.map(|l| l.saturating_sub(minted)),
+        phases,
+    })
+}
+
+/// Returns QueryResult displaying a referrer's code, number of packs referred and rewards
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address_raw` - a reference to the authenticated referrer's address
+fn query_referral_stats<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address_raw: &CanonicalAddr,
+) -> QueryResult {
+    let ref_store = ReadonlyPrefixedStorage::new(PREFIX_REFERRERS, &deps.storage);
+    let may_referrer: Option<StoredReferrer> = may_load(&ref_store, address_raw.as_slice())?;
+    let (code, packs_referred, rewards) = if let Some(referrer) = may_referrer {
+        (
+            Some(referrer.code),
+            referrer.packs,
+            referrer
+                .rewards
+                .iter()
+                .map(|r| r.get_humanized(&deps.api))
+                .collect::<StdResult<Vec<ReferralReward>>>()?,
+        )
+    } else {
+        (None, 0, Vec::new())
+    };
+
+    to_binary(&QueryAnswer::ReferralStats {
+        code,
+        packs_referred,
+        rewards,
+    })
+}
+
 /// Returns QueryResult displaying the contract's config
 ///
 /// # Arguments
@@ -715,32 +4010,95 @@ fn query_config<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> Query
             .map(|v| v.into_humanized(&deps.api))
             .collect::<StdResult<Vec<ContractInfo>>>()?,
         multi_sig_contract: deps.api.human_address(&config.multi_sig)?,
+        multi_sig_code_hash: config.multi_sig_code_hash,
         minting_has_halted: config.minting_halt,
         upgrades_have_halted: config.upgrade_halt,
+        prices: config.prices,
+        discount: config.discount,
+        discount_ends: config.discount_ends,
+        revenue_split: config
+            .revenue_split
+            .into_iter()
+            .map(|r| r.into_humanized(&deps.api))
+            .collect::<StdResult<Vec<RevenueShare>>>()?,
+        token_prices: config
+            .token_prices
+            .into_iter()
+            .map(|t| t.into_humanized(&deps.api))
+            .collect::<StdResult<Vec<TokenPrice>>>()?,
+        max_supply: config.max_supply,
+        address_limit: config.address_limit,
+        max_packs_per_tx: config.max_packs_per_tx,
+        referral_share: config.referral_share,
+        pay_referrals_immediately: config.pay_referrals_immediately,
+        minters: config
+            .minters
+            .iter()
+            .map(|m| deps.api.human_address(m))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        elements: config.elements,
     })
 }
 
-fn get_mints(bytes: &[u8], name: String, owner: &HumanAddr) -> StdResult<Mint> {
+/// Returns Option<String> of the element selected by a random byte, if there are any elements
+///
+/// # Arguments
+///
+/// * `elements` - the elements that can be assigned
+/// * `byte` - the random byte
+fn pick_element(elements: &[String], byte: u8) -> Option<String> {
+    if elements.is_empty() {
+        None
+    } else {
+        elements.get(byte as usize % elements.len()).cloned()
+    }
+}
+
+fn get_mints(
+    bytes: &[u8],
+    tier: &PackTier,
+    elements: &[String],
+    guaranteed: bool,
+    token_id: String,
+    name: String,
+    owner: &HumanAddr,
+) -> StdResult<Mint> {
     let pub_meta = Metadata {
         name: Some(name.clone()),
         description: None,
         image: None,
     };
-    let num_rolls = 5usize;
+    let num_rolls = tier.rolls as usize;
+    let floor = if guaranteed {
+        tier.floor.max(tier.guaranteed_floor)
+    } else {
+        tier.floor
+    };
+    let range = tier.cap - floor + 1;
     let mut skills: Vec<u8> = Vec::new();
     for i in 0..4 {
-        let val = bytes
+        let rolls = bytes
             .iter()
             .skip(i * num_rolls)
             .take(num_rolls)
-            .map(|b| (b % 100) + 1)
-            .min()
-            .unwrap_or(1);
+            .map(|b| (b % range) + floor);
+        let val = match tier.aggregation {
+            Aggregation::Min => rolls.min(),
+            Aggregation::Max => rolls.max(),
+        }
+        .unwrap_or(floor);
         skills.push(val);
     }
+    // the byte after the skill rolls selects the element
+    let element = bytes
+        .get(4 * num_rolls)
+        .and_then(|b| pick_element(elements, *b));
     let stats = Stats {
         base: skills.clone(),
         current: skills,
+        element,
+        xp: 0,
+        level: 0,
     };
     let stats_str = serde_json::to_string(&stats)
         .map_err(|e| StdError::generic_err(format!("Error serializing card stats: {}", e)))?;
@@ -750,7 +4108,7 @@ fn get_mints(bytes: &[u8], name: String, owner: &HumanAddr) -> StdResult<Mint> {
         image: Some(stats_str),
     };
     let mint = Mint {
-        token_id: None,
+        token_id: Some(token_id),
         owner: Some(owner.clone()),
         public_metadata: Some(pub_meta),
         private_metadata: Some(priv_meta),
@@ -834,3 +4192,54 @@ pub struct VersionBurn {
     pub burns: Burn,
     pub stored: StoreContractInfo,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(address: &str) -> Vec<u8> {
+        sha_256(address.as_bytes()).to_vec()
+    }
+
+    fn branch(a: &[u8], b: &[u8]) -> Vec<u8> {
+        if a <= b {
+            sha_256(&[a, b].concat()).to_vec()
+        } else {
+            sha_256(&[b, a].concat()).to_vec()
+        }
+    }
+
+    // a four leaf tree and the proof for its first leaf
+    fn tree() -> (Binary, Vec<Binary>) {
+        let leaves: Vec<Vec<u8>> = ["secret1alice", "secret1bob", "secret1carol", "secret1dave"]
+            .iter()
+            .map(|a| leaf(a))
+            .collect();
+        let left = branch(&leaves[0], &leaves[1]);
+        let right = branch(&leaves[2], &leaves[3]);
+        let root = Binary(branch(&left, &right));
+        (root, vec![Binary(leaves[1].clone()), Binary(right)])
+    }
+
+    #[test]
+    fn test_verify_merkle_proof() {
+        let (root, proof) = tree();
+        let alice = HumanAddr("secret1alice".to_string());
+        assert!(verify_merkle_proof(&alice, &proof, &root));
+    }
+
+    #[test]
+    fn test_verify_merkle_proof_wrong_leaf() {
+        let (root, proof) = tree();
+        let eve = HumanAddr("secret1eve".to_string());
+        assert!(!verify_merkle_proof(&eve, &proof, &root));
+    }
+
+    #[test]
+    fn test_verify_merkle_proof_wrong_sibling_order() {
+        let (root, mut proof) = tree();
+        proof.reverse();
+        let alice = HumanAddr("secret1alice".to_string());
+        assert!(!verify_merkle_proof(&alice, &proof, &root));
+    }
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, StdResult};
 
 /// code hash and address of a secret contract
-#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct ContractInfo {
     /// contract's code hash string
     pub code_hash: String,
@@ -2,10 +2,13 @@
 pub mod contract;
 mod contract_info;
 pub mod msg;
+mod multisig;
+pub mod permit;
 mod rand;
 mod snip721;
 pub mod state;
 pub mod stats;
+mod utils;
 mod viewing_key;
 
 #[cfg(target_arch = "wasm32")]
@@ -2,7 +2,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::contract_info::ContractInfo;
-use cosmwasm_std::HumanAddr;
+use crate::permit::Permit;
+use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
 
 /// Instantiation message
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -13,16 +14,48 @@ pub struct InitMsg {
     pub card_contract: ContractInfo,
     /// address of the multisig contract
     pub multi_sig: HumanAddr,
+    /// optional code hash of the multisig contract.  If provided, pack sales are deposited
+    /// through the multisig so they are recorded in its ledger
+    pub multi_sig_code_hash: Option<String>,
 }
 
 /// Handle messages
 #[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
-    /// mint a pack of 3 cards
+    /// mint packs of 3 cards
     Mint {
-        /// names to give the cards.  Must provide 3 names
+        /// names to give the cards.  Must provide 3 names for each pack
         names: Vec<String>,
+        /// optional name of the pack tier to buy.  Defaults to the first tier
+        tier: Option<String>,
+        /// Merkle proof of the buyer's address if the active sale phase requires one
+        proof: Option<Vec<Binary>>,
+        /// optional referral code of the player who referred the buyer
+        referral: Option<String>,
+        /// optional number of packs to buy.  Defaults to 1
+        packs: Option<u32>,
+        /// optional address that will own the cards.  Defaults to the buyer
+        recipient: Option<HumanAddr>,
+    },
+    /// create a viewing key
+    CreateViewingKey {
+        /// entropy String used in random key generation
+        entropy: String,
+    },
+    /// set viewing key
+    SetViewingKey {
+        /// desired viewing key
+        key: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// disallow the use of a query permit
+    RevokePermit {
+        /// name of the permit to revoke
+        permit_name: String,
+        /// optional message length padding
+        padding: Option<String>,
     },
     /// change address with administrative power
     ChangeAdmin {
@@ -43,6 +76,9 @@ pub enum HandleMsg {
     NewMultiSig {
         /// new multi sig contract address
         address: HumanAddr,
+        /// optional code hash of the new multi sig contract.  If provided, pack sales are
+        /// deposited through the multi sig so they are recorded in its ledger
+        code_hash: Option<String>,
     },
     /// halt/start minting and/or upgrading
     SetMintAndUpgradeStatus {
@@ -51,24 +87,513 @@ pub enum HandleMsg {
         /// true if upgrades should be halted
         stop_upgrade: Option<bool>,
     },
+    /// set the accepted payments for a pack
+    SetPackPrices {
+        /// full price of a pack in each accepted denomination
+        prices: Vec<Coin>,
+    },
+    /// set the SNIP-20 tokens accepted as payment for a pack
+    SetTokenPrices {
+        /// full price of a pack in each accepted token.  Replaces the current list
+        tokens: Vec<TokenPrice>,
+    },
+    /// buy a pack with a SNIP-20 token.  Called by the token contract
+    Receive {
+        /// address that sent the tokens
+        sender: HumanAddr,
+        /// owner of the tokens sent
+        from: HumanAddr,
+        /// amount of tokens sent
+        amount: Uint128,
+        /// base64 encoded ReceiveMsg
+        msg: Option<Binary>,
+    },
+    /// add a pack tier or replace the tier with the same name
+    SetPackTier {
+        /// the tier's parameters
+        tier: PackTier,
+    },
+    /// remove a pack tier
+    RemovePackTier {
+        /// name of the tier to remove
+        name: String,
+    },
+    /// add an upgrade recipe or replace the recipe with the same id
+    SetUpgradeRecipe {
+        /// the recipe's parameters
+        recipe: UpgradeRecipe,
+    },
+    /// remove an upgrade recipe
+    RemoveUpgradeRecipe {
+        /// id of the recipe to remove
+        id: u32,
+    },
+    /// set the addresses besides the admin that can airdrop heroes
+    SetMinters {
+        /// addresses that can airdrop heroes
+        minters: Vec<HumanAddr>,
+    },
+    /// mint free heroes to a list of recipients (admin or minters only)
+    Airdrop {
+        /// heroes to mint
+        drops: Vec<AirdropEntry>,
+    },
+    /// register promo codes for free packs
+    AddPromoCodes {
+        /// the codes to register
+        codes: Vec<PromoCode>,
+    },
+    /// redeem a promo code for a free pack
+    RedeemCode {
+        /// the promo code
+        code: String,
+        /// names to give the cards.  Must provide 3 names
+        names: Vec<String>,
+    },
+    /// set the rules hero names must follow
+    SetNameRules {
+        /// the name rules
+        rules: NameRules,
+    },
+    /// add substrings that can not appear in hero names
+    AddBannedSubstrings {
+        /// substrings to ban
+        substrings: Vec<String>,
+    },
+    /// remove substrings from the banned list
+    RemoveBannedSubstrings {
+        /// substrings to allow again
+        substrings: Vec<String>,
+    },
+    /// pay the rename fee to give a hero a new name
+    RenameHero {
+        /// the hero to rename
+        hero: HeroInfo,
+        /// the hero's new name
+        name: String,
+    },
+    /// set the elements that can be assigned to newly minted heroes.  If the list is empty,
+    /// new heroes will not have an element
+    SetElements {
+        /// the elements new heroes can have
+        elements: Vec<String>,
+    },
+    /// set the maximum total pack supply and the number of packs an address can mint
+    SetMintLimits {
+        /// optional maximum number of packs that can ever be minted
+        max_supply: Option<u32>,
+        /// optional maximum number of packs an address can mint
+        address_limit: Option<u32>,
+        /// optional maximum number of packs that can be bought in one transaction.  Defaults
+        /// to 1
+        max_packs_per_tx: Option<u32>,
+    },
+    /// set the timed sale phases.  If there are no phases, the sale is always open
+    SetSalePhases {
+        /// the sale phases.  Replaces the current list
+        phases: Vec<SalePhase>,
+    },
+    /// add addresses to a sale phase's allowlist
+    AddToAllowlist {
+        /// name of the sale phase
+        phase: String,
+        /// addresses allowed to mint in the phase
+        addresses: Vec<HumanAddr>,
+    },
+    /// remove addresses from a sale phase's allowlist
+    RemoveFromAllowlist {
+        /// name of the sale phase
+        phase: String,
+        /// addresses no longer allowed to mint in the phase
+        addresses: Vec<HumanAddr>,
+    },
+    /// register a referral code.  Replaces any code the sender registered before
+    RegisterReferralCode {
+        /// the referral code.  Must be 3 to 20 letters, digits, dashes or underscores
+        code: String,
+    },
+    /// claim the referral rewards that have accrued to the sender
+    ClaimReferralRewards {},
+    /// set the referrer's share of the pack price
+    SetReferralTerms {
+        /// referrer's share of the pack price in basis points
+        share: u16,
+        /// true if referrers should be paid with each sale instead of accruing rewards
+        pay_immediately: bool,
+    },
+    /// set a discount off the pack price
+    SetDiscount {
+        /// discount in basis points.  0 removes the discount
+        discount: u16,
+        /// optional number of seconds since epoch time 01/01/1970 after which the discount ends
+        ends: Option<u64>,
+    },
+    /// set the recipients of pack sale revenue
+    SetRevenueSplit {
+        /// recipients and their shares in basis points.  Shares must total 10000
+        recipients: Vec<RevenueShare>,
+    },
     /// add number of packs minted (admin only)
     AddMintCount {
         /// number of packs minted in previous contracts
         packs_minted: u32,
     },
-    /// burn two heroes to upgrade a third
+    /// burn heroes to upgrade another
     Upgrade {
         /// heroes to burn
         burn: Vec<HeroInfo>,
         /// hero to upgrade
         upgrade: HeroInfo,
+        /// optional id of the upgrade recipe to use.  Defaults to the first recipe
+        recipe: Option<u32>,
         /// entropy for the rng
         entropy: String,
     },
+    /// burn two heroes to mint a new hero descended from them
+    Fuse {
+        /// heroes to fuse.  Must provide 2 heroes
+        parents: Vec<HeroInfo>,
+        /// name to give the new hero
+        name: String,
+        /// entropy for the rng
+        entropy: String,
+    },
+    /// burn a hero on a legacy card contract and mint the same hero on the current one
+    MigrateHero {
+        /// the hero to migrate
+        hero: HeroInfo,
+    },
 }
 
-/// the token ID and contract address of a Hero
+/// msgs that can be sent with a SNIP-20 payment
 #[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// mint packs of cards
+    Mint {
+        /// names to give the cards.  Must provide 3 names for each pack
+        names: Vec<String>,
+        /// optional name of the pack tier to buy.  Defaults to the first tier
+        tier: Option<String>,
+        /// Merkle proof of the buyer's address if the active sale phase requires one
+        proof: Option<Vec<Binary>>,
+        /// optional referral code of the player who referred the buyer
+        referral: Option<String>,
+        /// optional number of packs to buy.  Defaults to 1
+        packs: Option<u32>,
+        /// optional address that will own the cards.  Defaults to the buyer
+        recipient: Option<HumanAddr>,
+    },
+}
+
+/// how a skill's rolls are combined
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    /// keep the lowest roll
+    Min,
+    /// keep the highest roll
+    Max,
+}
+
+/// a kind of pack with its own price and stat-roll distribution
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct PackTier {
+    /// name of the tier
+    pub name: String,
+    /// price of this tier in basis points of the pack prices.  10000 is the listed price
+    pub price_bps: u32,
+    /// number of rolls made for each skill.  Must be from 1 to 10
+    pub rolls: u8,
+    /// how a skill's rolls are combined
+    pub aggregation: Aggregation,
+    /// lowest possible skill value
+    pub floor: u8,
+    /// highest possible skill value.  Can not exceed 100
+    pub cap: u8,
+    /// number of cards in the pack whose skills are rolled with the guaranteed floor
+    pub guaranteed_slots: u8,
+    /// lowest possible skill value of a guaranteed card
+    pub guaranteed_floor: u8,
+    /// optional maximum number of packs of this tier that can be minted
+    pub supply: Option<u32>,
+}
+
+/// heroes to airdrop to one recipient.  Each entry counts as one pack against the supply limits
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct AirdropEntry {
+    /// address that will own the heroes
+    pub recipient: HumanAddr,
+    /// optional names to give the heroes.  One hero is minted for each name
+    pub names: Option<Vec<String>>,
+    /// optional number of heroes to mint if no names are given.  Defaults to a full pack of 3
+    pub heroes: Option<u8>,
+    /// optional name of the pack tier whose stats distribution is used.  Defaults to the first
+    /// tier
+    pub tier: Option<String>,
+}
+
+/// the rules hero names must follow
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct NameRules {
+    /// minimum number of characters in a name
+    pub min_length: u16,
+    /// maximum number of characters in a name
+    pub max_length: u16,
+    /// characters allowed in names besides ASCII letters and digits
+    pub extra_chars: String,
+    /// true if no two heroes may have the same name.  Names are compared ignoring case and
+    /// any characters that are not letters or digits
+    pub unique: bool,
+    /// uscrt fee to rename a hero
+    pub rename_fee: Uint128,
+}
+
+/// a promo code for a free pack
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct PromoCode {
+    /// sha256 hash of the code
+    pub hash: Binary,
+    /// optional name of the pack tier the code mints.  Defaults to the first tier
+    pub tier: Option<String>,
+    /// number of times the code can be redeemed.  Each address can only redeem it once
+    pub uses: u32,
+    /// optional number of seconds since epoch time 01/01/1970 after which the code expires
+    pub expires: Option<u64>,
+}
+
+/// a promo code displayed in queries
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct PromoCodeInfo {
+    /// index of the code
+    pub id: u32,
+    /// sha256 hash of the code
+    pub hash: Binary,
+    /// optional name of the pack tier the code mints
+    pub tier: Option<String>,
+    /// number of times the code can be redeemed
+    pub uses: u32,
+    /// number of times the code has been redeemed
+    pub redeemed: u32,
+    /// optional number of seconds since epoch time 01/01/1970 after which the code expires
+    pub expires: Option<u64>,
+}
+
+/// a power difference bracket of an upgrade recipe
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct PowerBracket {
+    /// the bracket applies when the power difference is greater than this
+    pub above: i16,
+    /// base number of points added to each skill
+    pub base_upgrade: i8,
+}
+
+/// an extra upgrade a recipe gives one skill
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct SkillBoost {
+    /// index of the boosted skill
+    pub skill: u8,
+    /// points added to the skill's upgrade
+    pub boost: i8,
+}
+
+/// the parameters of an upgrade recipe
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct UpgradeRecipe {
+    /// id of the recipe
+    pub id: u32,
+    /// number of heroes that must be burned.  Must be from 1 to 5
+    pub burns: u8,
+    /// optional uscrt fee to do the upgrade
+    pub fee: Option<Uint128>,
+    /// adjustments to the base upgrade.  One is picked at random for each skill
+    pub adjust: Vec<i8>,
+    /// power difference brackets, sorted from the highest lower bound when the recipe is set.
+    /// The first bracket whose lower bound the power difference exceeds sets the base upgrade.
+    /// The power difference is the number of burns times the upgraded hero's skill total minus
+    /// the skill total of the burned heroes
+    pub brackets: Vec<PowerBracket>,
+    /// base upgrade if the power difference does not exceed any bracket
+    pub default_upgrade: i8,
+    /// extra upgrades given to specific skills
+    #[serde(default)]
+    pub boosts: Vec<SkillBoost>,
+}
+
+/// a pack tier displayed in queries
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct PackTierInfo {
+    /// the tier's parameters
+    pub tier: PackTier,
+    /// number of packs of this tier minted
+    pub minted: u32,
+    /// number of packs of this tier that can still be minted, if the supply is limited
+    pub remaining: Option<u32>,
+}
+
+/// who may mint during a sale phase
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseAccess {
+    /// anyone
+    Public,
+    /// addresses added to the phase's allowlist
+    Allowlist,
+    /// addresses that can prove they are in a Merkle tree.  Leaves are the sha256 of the
+    /// address, and each pair of hashes is sorted before hashing
+    Merkle { root: Binary },
+}
+
+/// a timed sale phase
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct SalePhase {
+    /// name of the phase
+    pub name: String,
+    /// number of seconds since epoch time 01/01/1970 when the phase starts
+    pub start: u64,
+    /// optional number of seconds since epoch time 01/01/1970 when the phase ends
+    pub end: Option<u64>,
+    /// price during this phase in basis points of the pack tier price
+    pub price_bps: u32,
+    /// who may mint during this phase
+    pub access: PhaseAccess,
+    /// optional maximum number of packs an address can mint during this phase
+    pub address_limit: Option<u32>,
+}
+
+/// an address' mints during a sale phase
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct PhaseAllowance {
+    /// name of the phase
+    pub name: String,
+    /// true if the address is on the phase's allowlist.  None if the phase has no allowlist
+    pub allowlisted: Option<bool>,
+    /// number of packs the address minted during this phase
+    pub minted: u32,
+    /// number of packs the address can still mint during this phase, if limited
+    pub remaining: Option<u32>,
+}
+
+/// a referrer's rewards in one currency
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct ReferralReward {
+    /// native coin denomination.  None if the rewards are in a SNIP-20 token
+    pub denom: Option<String>,
+    /// SNIP-20 token contract address.  None if the rewards are in a native coin
+    pub token: Option<HumanAddr>,
+    /// total amount earned
+    pub earned: Uint128,
+    /// amount accrued that has not been claimed
+    pub unclaimed: Uint128,
+}
+
+/// a SNIP-20 token accepted as payment for a pack
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct TokenPrice {
+    /// token contract's code hash and address
+    pub contract: ContractInfo,
+    /// full price of a pack in this token
+    pub price: Uint128,
+}
+
+/// a recipient of pack sale revenue
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct RevenueShare {
+    /// recipient's address
+    pub address: HumanAddr,
+    /// recipient's share in basis points
+    pub share: u16,
+}
+
+/// a payment for a pack.  Airdropped packs have an amount of 0 and no denom or token
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct Payment {
+    /// amount paid
+    pub amount: Uint128,
+    /// native coin denomination.  None if paid with a SNIP-20 token
+    pub denom: Option<String>,
+    /// SNIP-20 token contract address.  None if paid with a native coin
+    pub token: Option<HumanAddr>,
+}
+
+/// a pack minted
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct MintRecord {
+    /// index of the mint in the minter's history
+    pub id: u64,
+    /// owner of the minted cards
+    pub owner: HumanAddr,
+    /// number of seconds since epoch time 01/01/1970 in which the pack was minted
+    pub time: u64,
+    /// name of the pack tier
+    pub tier: String,
+    /// names given to the minted cards
+    pub names: Vec<String>,
+    /// price paid for the pack
+    pub price: Payment,
+}
+
+/// an upgrade done
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct UpgradeRecord {
+    /// index of the upgrade in the minter's history
+    pub id: u64,
+    /// owner of the heroes
+    pub owner: HumanAddr,
+    /// number of seconds since epoch time 01/01/1970 in which the upgrade happened
+    pub time: u64,
+    /// hero that was upgraded
+    pub upgraded: HeroInfo,
+    /// heroes that were burned
+    pub burned: Vec<HeroInfo>,
+    /// hero's skills before the upgrade
+    pub pre_upgrade_skills: Vec<u8>,
+    /// hero's skills after the upgrade
+    pub post_upgrade_skills: Vec<u8>,
+}
+
+/// the lineage saved in a fused hero's private metadata
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct Lineage {
+    /// number of fusions in the hero's longest line of ancestors
+    pub generation: u32,
+    /// heroes that were fused to create this one
+    pub parents: Vec<HeroInfo>,
+}
+
+/// a hero in an ancestry tree
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct LineageNode {
+    /// the hero
+    pub hero: HeroInfo,
+    /// number of fusions in the hero's longest line of ancestors.  0 if the hero was minted
+    /// from a pack
+    pub generation: u32,
+    /// the hero's parents.  Empty if the hero was minted from a pack or the tree was cut off at
+    /// the requested depth
+    pub parents: Vec<LineageNode>,
+}
+
+/// a possible value of a skill after an upgrade
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct SkillOutcome {
+    /// skill value after the upgrade
+    pub value: u8,
+    /// number of upgrade rolls that result in this value
+    pub chances: u32,
+}
+
+/// the possible outcomes of upgrading a skill
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct SkillPreview {
+    /// skill value before the upgrade
+    pub current: u8,
+    /// possible skill values after the upgrade in ascending order
+    pub outcomes: Vec<SkillOutcome>,
+}
+
+/// the token ID and contract address of a Hero
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct HeroInfo {
     /// the hero's token ID
     pub token_id: String,
@@ -83,6 +608,173 @@ pub enum QueryMsg {
     Config {},
     /// display the number of packs minted
     PacksMinted {},
+    /// display the pack tiers and their remaining supply
+    PackTiers {},
+    /// display the upgrade recipes
+    UpgradeRecipes {},
+    /// display the rules hero names must follow
+    NameRules {},
+    /// display the sale phases
+    SalePhases {},
+    /// display the number of packs an address has minted and can still mint
+    MintAllowance {
+        /// address whose allowance should be displayed
+        address: HumanAddr,
+        /// address' viewing key
+        viewing_key: String,
+    },
+    /// display a referrer's code, number of packs referred and rewards earned
+    ReferralStats {
+        /// address of the referrer
+        address: HumanAddr,
+        /// address' viewing key
+        viewing_key: String,
+    },
+    /// display the token a legacy hero was migrated to
+    MigratedHero {
+        /// the legacy hero
+        hero: HeroInfo,
+    },
+    /// display a hero's ancestors
+    Lineage {
+        /// the hero whose ancestors should be displayed
+        hero: HeroInfo,
+        /// optional number of generations to display.  Defaults to 5, maximum of 10
+        depth: Option<u32>,
+    },
+    /// display the packs an address minted
+    MintHistory {
+        /// address whose history should be displayed
+        address: HumanAddr,
+        /// address' viewing key
+        viewing_key: String,
+        /// optional page to display
+        page: Option<u32>,
+        /// optional number of mints per page
+        page_size: Option<u32>,
+    },
+    /// display the upgrades an address did
+    UpgradeHistory {
+        /// address whose history should be displayed
+        address: HumanAddr,
+        /// address' viewing key
+        viewing_key: String,
+        /// optional page to display
+        page: Option<u32>,
+        /// optional number of upgrades per page
+        page_size: Option<u32>,
+    },
+    /// display every address' mints (admin only)
+    DumpMintHistory {
+        /// admin's address
+        admin: HumanAddr,
+        /// admin's viewing key
+        viewing_key: String,
+        /// optional index of the mint to start display.  Use this for pagination
+        start_from: Option<u64>,
+        /// optional number of mints to display
+        limit: Option<u64>,
+    },
+    /// display every address' upgrades (admin only)
+    DumpUpgradeHistory {
+        /// admin's address
+        admin: HumanAddr,
+        /// admin's viewing key
+        viewing_key: String,
+        /// optional index of the upgrade to start display.  Use this for pagination
+        start_from: Option<u64>,
+        /// optional number of upgrades to display
+        limit: Option<u64>,
+    },
+    /// display the promo codes and how often they were redeemed (admin only)
+    PromoCodes {
+        /// admin's address
+        admin: HumanAddr,
+        /// admin's viewing key
+        viewing_key: String,
+        /// optional index of the code to start display.  Use this for pagination
+        start_from: Option<u32>,
+        /// optional number of codes to display
+        limit: Option<u32>,
+    },
+    /// display the possible outcomes of an upgrade without doing it
+    PreviewUpgrade {
+        /// address that owns the heroes
+        address: HumanAddr,
+        /// address' viewing key
+        viewing_key: String,
+        /// heroes to burn
+        burn: Vec<HeroInfo>,
+        /// hero to upgrade
+        upgrade: HeroInfo,
+        /// optional id of the upgrade recipe to use.  Defaults to the first recipe
+        recipe: Option<u32>,
+    },
+    /// perform a query authenticated by a permit
+    WithPermit {
+        /// permit signed by the querier
+        permit: Permit,
+        /// the query to perform
+        query: QueryWithPermit,
+    },
+}
+
+/// queries that can be authenticated by a permit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryWithPermit {
+    /// display the number of packs the querier has minted and can still mint.  Requires the
+    /// history permission
+    MintAllowance {},
+    /// display the querier's referral code, number of packs referred and rewards earned.
+    /// Requires the referrals permission
+    ReferralStats {},
+    /// display the packs the querier minted.  Requires the history permission
+    MintHistory {
+        /// optional page to display
+        page: Option<u32>,
+        /// optional number of mints per page
+        page_size: Option<u32>,
+    },
+    /// display the upgrades the querier did.  Requires the history permission
+    UpgradeHistory {
+        /// optional page to display
+        page: Option<u32>,
+        /// optional number of upgrades per page
+        page_size: Option<u32>,
+    },
+    /// display every address' mints.  Requires the owner permission signed by the admin
+    DumpMintHistory {
+        /// optional index of the mint to start display.  Use this for pagination
+        start_from: Option<u64>,
+        /// optional number of mints to display
+        limit: Option<u64>,
+    },
+    /// display every address' upgrades.  Requires the owner permission signed by the admin
+    DumpUpgradeHistory {
+        /// optional index of the upgrade to start display.  Use this for pagination
+        start_from: Option<u64>,
+        /// optional number of upgrades to display
+        limit: Option<u64>,
+    },
+    /// display the promo codes and how often they were redeemed.  Requires the owner
+    /// permission signed by the admin
+    PromoCodes {
+        /// optional index of the code to start display.  Use this for pagination
+        start_from: Option<u32>,
+        /// optional number of codes to display
+        limit: Option<u32>,
+    },
+    /// display the possible outcomes of an upgrade without doing it.  Requires the history
+    /// permission
+    PreviewUpgrade {
+        /// heroes to burn
+        burn: Vec<HeroInfo>,
+        /// hero to upgrade
+        upgrade: HeroInfo,
+        /// optional id of the upgrade recipe to use.  Defaults to the first recipe
+        recipe: Option<u32>,
+    },
 }
 
 /// success or failure response
@@ -99,6 +791,12 @@ pub enum HandleAnswer {
     Mint {
         status: ResponseStatus,
     },
+    ViewingKey {
+        key: String,
+    },
+    RevokePermit {
+        permit_name: String,
+    },
     ChangeAdmin {
         new_admin: HumanAddr,
     },
@@ -115,6 +813,86 @@ pub enum HandleAnswer {
     AddMintCount {
         packs_added: u32,
     },
+    SetPackPrices {
+        prices: Vec<Coin>,
+    },
+    SetTokenPrices {
+        tokens: Vec<TokenPrice>,
+    },
+    SetPackTier {
+        tier: PackTier,
+    },
+    RemovePackTier {
+        name: String,
+    },
+    SetUpgradeRecipe {
+        recipe: UpgradeRecipe,
+    },
+    RemoveUpgradeRecipe {
+        id: u32,
+    },
+    SetMintLimits {
+        max_supply: Option<u32>,
+        address_limit: Option<u32>,
+        max_packs_per_tx: u32,
+    },
+    SetMinters {
+        minters: Vec<HumanAddr>,
+    },
+    Airdrop {
+        /// number of heroes minted
+        heroes: u32,
+    },
+    AddPromoCodes {
+        /// number of codes registered
+        count: u32,
+    },
+    RedeemCode {
+        status: ResponseStatus,
+    },
+    SetNameRules {
+        rules: NameRules,
+    },
+    AddBannedSubstrings {
+        status: ResponseStatus,
+    },
+    RemoveBannedSubstrings {
+        status: ResponseStatus,
+    },
+    RenameHero {
+        status: ResponseStatus,
+    },
+    SetElements {
+        elements: Vec<String>,
+    },
+    SetSalePhases {
+        phases: Vec<SalePhase>,
+    },
+    AddToAllowlist {
+        phase: String,
+        added: u32,
+    },
+    RemoveFromAllowlist {
+        phase: String,
+        removed: u32,
+    },
+    RegisterReferralCode {
+        code: String,
+    },
+    ClaimReferralRewards {
+        claimed: Vec<ReferralReward>,
+    },
+    SetReferralTerms {
+        share: u16,
+        pay_immediately: bool,
+    },
+    SetDiscount {
+        discount: u16,
+        ends: Option<u64>,
+    },
+    SetRevenueSplit {
+        recipients: Vec<RevenueShare>,
+    },
     AddLegacyCardContracts {
         card_versions: Vec<ContractInfo>,
     },
@@ -124,6 +902,18 @@ pub enum HandleAnswer {
         /// hero's skills after the upgrade
         post_upgrade_skills: Vec<u8>,
     },
+    Fuse {
+        /// token ID of the new hero
+        token_id: String,
+        /// new hero's skills
+        skills: Vec<u8>,
+        /// new hero's generation
+        generation: u32,
+    },
+    MigrateHero {
+        /// the hero's token on the current card contract
+        migrated_to: HeroInfo,
+    },
 }
 
 /// Responses from queries
@@ -134,9 +924,83 @@ pub enum QueryAnswer {
     Config {
         card_versions: Vec<ContractInfo>,
         multi_sig_contract: HumanAddr,
+        multi_sig_code_hash: Option<String>,
         minting_has_halted: bool,
         upgrades_have_halted: bool,
+        /// full price of a pack in each accepted denomination
+        prices: Vec<Coin>,
+        /// discount off the pack price in basis points
+        discount: u16,
+        /// optional number of seconds since epoch time 01/01/1970 after which the discount ends
+        discount_ends: Option<u64>,
+        /// recipients of pack sale revenue
+        revenue_split: Vec<RevenueShare>,
+        /// full price of a pack in each accepted SNIP-20 token
+        token_prices: Vec<TokenPrice>,
+        /// optional maximum number of packs that can ever be minted
+        max_supply: Option<u32>,
+        /// optional maximum number of packs an address can mint
+        address_limit: Option<u32>,
+        /// maximum number of packs that can be bought in one transaction
+        max_packs_per_tx: u32,
+        /// referrer's share of the pack price in basis points
+        referral_share: u16,
+        /// true if referrers are paid with each sale instead of accruing rewards
+        pay_referrals_immediately: bool,
+        /// addresses besides the admin that can airdrop heroes
+        minters: Vec<HumanAddr>,
+        /// elements that can be assigned to newly minted heroes
+        elements: Vec<String>,
     },
     /// number of packs minted
     PacksMinted { packs_minted: u32 },
+    /// pack tiers
+    PackTiers { tiers: Vec<PackTierInfo> },
+    /// upgrade recipes
+    UpgradeRecipes { recipes: Vec<UpgradeRecipe> },
+    /// hero name rules
+    NameRules { rules: NameRules },
+    /// sale phases
+    SalePhases { phases: Vec<SalePhase> },
+    /// an address' mint allowance
+    MintAllowance {
+        /// number of packs the address has minted
+        minted: u32,
+        /// number of packs the address can still mint, if limited
+        remaining: Option<u32>,
+        /// the address' mints during each sale phase
+        phases: Vec<PhaseAllowance>,
+    },
+    /// a referrer's stats
+    ReferralStats {
+        /// the referrer's code, if registered
+        code: Option<String>,
+        /// number of packs bought with the referrer's code
+        packs_referred: u32,
+        /// rewards earned in each currency
+        rewards: Vec<ReferralReward>,
+    },
+    /// packs minted
+    MintHistory { history: Vec<MintRecord> },
+    /// upgrades done
+    UpgradeHistory { history: Vec<UpgradeRecord> },
+    /// a hero's ancestors
+    Lineage { lineage: LineageNode },
+    /// the token a legacy hero was migrated to, if it was migrated
+    MigratedHero { migrated_to: Option<HeroInfo> },
+    /// promo codes
+    PromoCodes { codes: Vec<PromoCodeInfo> },
+    /// possible outcomes of an upgrade
+    PreviewUpgrade {
+        /// id of the upgrade recipe
+        recipe: u32,
+        /// number of burns times the upgraded hero's skill total minus the burned heroes' total
+        power_diff: i16,
+        /// number of points the recipe adds to every skill for this power difference
+        base_upgrade: i8,
+        /// number of equally likely upgrade rolls for each skill
+        out_of: u32,
+        /// possible outcomes of each skill
+        skills: Vec<SkillPreview>,
+    },
 }
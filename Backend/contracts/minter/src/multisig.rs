@@ -0,0 +1,15 @@
+use crate::contract::BLOCK_SIZE;
+use secret_toolkit::utils::HandleCallback;
+use serde::Serialize;
+
+/// multisig handle msgs
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiSigHandleMsg {
+    /// send native coins to the treasury and record them in its ledger
+    Deposit { memo: Option<String> },
+}
+
+impl HandleCallback for MultiSigHandleMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
@@ -2,14 +2,69 @@ use std::any::type_name;
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, ReadonlyStorage, StdError, StdResult, Storage};
+use cosmwasm_std::{
+    Api, CanonicalAddr, Coin, ReadonlyStorage, StdError, StdResult, Storage, Uint128,
+};
 
 use crate::contract_info::StoreContractInfo;
-use secret_toolkit::serialization::{Bincode2, Serde};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+
+use crate::msg::{
+    HeroInfo, MintRecord, PackTier, Payment, ReferralReward, RevenueShare, TokenPrice,
+    UpgradeRecord,
+};
+use secret_toolkit::{
+    serialization::{Bincode2, Json, Serde},
+    storage::{AppendStore, AppendStoreMut},
+};
 
 pub const CONFIG_KEY: &[u8] = b"config";
 pub const ADMIN_KEY: &[u8] = b"admin";
 pub const VKEY_KEY: &[u8] = b"vkey";
+pub const MY_ADDRESS_KEY: &[u8] = b"myaddr";
+pub const PREFIX_VIEW_KEY: &[u8] = b"viewkey";
+/// prefix for the mint history
+pub const PREFIX_MINT_HISTORY: &[u8] = b"minthist";
+/// prefix for the indices of each address' mints
+pub const PREFIX_MINT_IDS: &[u8] = b"mintids";
+/// prefix for the upgrade history
+pub const PREFIX_UPGRADE_HISTORY: &[u8] = b"upghist";
+/// prefix for the indices of each address' upgrades
+pub const PREFIX_UPGRADE_IDS: &[u8] = b"upgids";
+/// key to store the pack tiers
+pub const TIERS_KEY: &[u8] = b"tiers";
+/// prefix for the parents of fused heroes
+pub const PREFIX_LINEAGE: &[u8] = b"lineage";
+/// prefix for the tokens heroes were migrated to
+pub const PREFIX_MIGRATIONS: &[u8] = b"migrated";
+/// prefix for the promo codes
+pub const PREFIX_PROMO_CODES: &[u8] = b"promos";
+/// prefix for the index of each promo code's hash
+pub const PREFIX_PROMO_IDS: &[u8] = b"promoids";
+/// prefix for the addresses that redeemed each promo code
+pub const PREFIX_PROMO_REDEEMERS: &[u8] = b"promoused";
+/// key to store the hero name rules
+pub const NAME_RULES_KEY: &[u8] = b"namerules";
+/// key to store the banned name substrings
+pub const BANNED_KEY: &[u8] = b"banned";
+/// prefix for the registry of normalized hero names
+pub const PREFIX_NAMES: &[u8] = b"names";
+/// key to store the upgrade recipes
+pub const RECIPES_KEY: &[u8] = b"recipes";
+/// key to store the sale phases
+pub const PHASES_KEY: &[u8] = b"phases";
+/// prefix for the number of packs each address minted
+pub const PREFIX_MINT_COUNTS: &[u8] = b"mintcnt";
+/// prefix for the number of packs each address minted during a sale phase
+pub const PREFIX_PHASE_MINTS: &[u8] = b"phasemint";
+/// prefix for sale phase allowlists
+pub const PREFIX_ALLOWLIST: &[u8] = b"allowlist";
+/// prefix for the owners of referral codes
+pub const PREFIX_REFERRAL_CODES: &[u8] = b"refcode";
+/// prefix for referrer stats
+pub const PREFIX_REFERRERS: &[u8] = b"referrer";
+/// total basis points of a revenue split
+pub const TOTAL_SHARES: u16 = 10000;
 
 /// minter state
 #[derive(Serialize, Deserialize)]
@@ -24,12 +79,473 @@ pub struct Config {
     //    pub tourney_halt: bool,
     /// multi sig contract address
     pub multi_sig: CanonicalAddr,
+    /// optional multi sig code hash used to record pack sales in its ledger
+    pub multi_sig_code_hash: Option<String>,
     /// tournament contract address
     //    pub tourney: CanonicalAddr,
     /// prng seed
     pub prng_seed: Vec<u8>,
     /// number of packs minted
     pub mint_cnt: u32,
+    /// accepted payments for a pack
+    pub prices: Vec<Coin>,
+    /// discount off the pack price in basis points
+    pub discount: u16,
+    /// optional number of seconds since epoch time 01/01/1970 after which the discount ends
+    pub discount_ends: Option<u64>,
+    /// recipients of pack sale revenue
+    pub revenue_split: Vec<StoreRevenueShare>,
+    /// SNIP-20 tokens accepted as payment for a pack
+    pub token_prices: Vec<StoreTokenPrice>,
+    /// optional maximum number of packs that can ever be minted
+    pub max_supply: Option<u32>,
+    /// optional maximum number of packs an address can mint
+    pub address_limit: Option<u32>,
+    /// referrer's share of the pack price in basis points
+    pub referral_share: u16,
+    /// true if referrers should be paid with each sale instead of accruing rewards
+    pub pay_referrals_immediately: bool,
+    /// number of mints recorded
+    pub mint_record_cnt: u64,
+    /// number of upgrades recorded
+    pub upgrade_record_cnt: u64,
+    /// number of heroes created by fusion
+    pub fuse_cnt: u32,
+    /// number of heroes migrated from legacy card contracts
+    pub migrate_cnt: u32,
+    /// addresses besides the admin that can airdrop heroes
+    pub minters: Vec<CanonicalAddr>,
+    /// number of promo codes registered
+    pub promo_cnt: u32,
+    /// maximum number of packs that can be bought in one transaction
+    pub max_packs_per_tx: u32,
+    /// elements that can be assigned to newly minted heroes
+    pub elements: Vec<String>,
+}
+
+impl Config {
+    /// Returns Vec<Coin> of the accepted payments for a pack after any active discount
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - current time in seconds since epoch time 01/01/1970
+    /// * `price_bps` - price of the pack tier in basis points of the pack prices
+    pub fn current_prices(&self, time: u64, price_bps: u32) -> Vec<Coin> {
+        let discount = self.active_discount(time);
+        self.prices
+            .iter()
+            .map(|p| Coin {
+                denom: p.denom.clone(),
+                amount: apply_discount(scale_price(p.amount, price_bps), discount),
+            })
+            .collect()
+    }
+
+    /// Returns Option<Uint128> of a SNIP-20 token's price for a pack after any active
+    /// discount.  Returns None if the token is not accepted
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - a reference to the token contract's address
+    /// * `time` - current time in seconds since epoch time 01/01/1970
+    /// * `price_bps` - price of the pack tier in basis points of the pack prices
+    pub fn current_token_price(
+        &self,
+        token: &CanonicalAddr,
+        time: u64,
+        price_bps: u32,
+    ) -> Option<Uint128> {
+        self.token_prices
+            .iter()
+            .find(|t| t.contract.address == *token)
+            .map(|t| apply_discount(scale_price(t.price, price_bps), self.active_discount(time)))
+    }
+
+    /// Returns u128 of the discount in basis points at the specified time
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - current time in seconds since epoch time 01/01/1970
+    fn active_discount(&self, time: u64) -> u128 {
+        if self.discount_ends.map_or(true, |e| time < e) {
+            self.discount as u128
+        } else {
+            0
+        }
+    }
+}
+
+/// Returns Uint128 amount after scaling a price by a number of basis points
+///
+/// # Arguments
+///
+/// * `amount` - listed price
+/// * `bps` - basis points of the listed price to charge
+pub fn scale_price(amount: Uint128, bps: u32) -> Uint128 {
+    Uint128(amount.u128() * bps as u128 / TOTAL_SHARES as u128)
+}
+
+/// Returns Uint128 amount after subtracting a discount
+///
+/// # Arguments
+///
+/// * `amount` - full price
+/// * `discount` - discount in basis points
+pub fn apply_discount(amount: Uint128, discount: u128) -> Uint128 {
+    Uint128(amount.u128() - amount.u128() * discount / TOTAL_SHARES as u128)
+}
+
+/// a SNIP-20 token accepted as payment for a pack
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoreTokenPrice {
+    /// token contract's code hash and address
+    pub contract: StoreContractInfo,
+    /// full price of a pack in this token
+    pub price: Uint128,
+}
+
+impl StoreTokenPrice {
+    /// Returns StdResult<TokenPrice> from converting a StoreTokenPrice to a displayable
+    /// TokenPrice
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - a reference to the Api used to convert human and canonical addresses
+    pub fn into_humanized<A: Api>(self, api: &A) -> StdResult<TokenPrice> {
+        Ok(TokenPrice {
+            contract: self.contract.into_humanized(api)?,
+            price: self.price,
+        })
+    }
+}
+
+/// a pack tier and the number of its packs minted
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredPackTier {
+    /// the tier's parameters
+    pub tier: PackTier,
+    /// number of packs of this tier minted
+    pub minted: u32,
+}
+
+/// a payment for a pack.  Airdropped packs have an amount of 0 and no denom or token
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredPayment {
+    /// amount paid
+    pub amount: Uint128,
+    /// native coin denomination.  None if paid with a SNIP-20 token
+    pub denom: Option<String>,
+    /// SNIP-20 token contract address.  None if paid with a native coin
+    pub token: Option<CanonicalAddr>,
+}
+
+/// a pack minted
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredMintRecord {
+    /// owner of the minted cards
+    pub owner: CanonicalAddr,
+    /// number of seconds since epoch time 01/01/1970 in which the pack was minted
+    pub time: u64,
+    /// name of the pack tier
+    pub tier: String,
+    /// names given to the minted cards
+    pub names: Vec<String>,
+    /// price paid for the pack
+    pub price: StoredPayment,
+}
+
+impl StoredMintRecord {
+    /// Returns StdResult<MintRecord> from converting a StoredMintRecord to a displayable
+    /// MintRecord
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - a reference to the Api used to convert human and canonical addresses
+    /// * `id` - index of the mint in the history
+    pub fn into_humanized<A: Api>(self, api: &A, id: u64) -> StdResult<MintRecord> {
+        let token = if let Some(tkn) = self.price.token {
+            Some(api.human_address(&tkn)?)
+        } else {
+            None
+        };
+        Ok(MintRecord {
+            id,
+            owner: api.human_address(&self.owner)?,
+            time: self.time,
+            tier: self.tier,
+            names: self.names,
+            price: Payment {
+                amount: self.price.amount,
+                denom: self.price.denom,
+                token,
+            },
+        })
+    }
+}
+
+/// an upgrade done
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredUpgradeRecord {
+    /// owner of the heroes
+    pub owner: CanonicalAddr,
+    /// number of seconds since epoch time 01/01/1970 in which the upgrade happened
+    pub time: u64,
+    /// hero that was upgraded
+    pub upgraded: HeroInfo,
+    /// heroes that were burned
+    pub burned: Vec<HeroInfo>,
+    /// hero's skills before the upgrade
+    pub pre_upgrade_skills: Vec<u8>,
+    /// hero's skills after the upgrade
+    pub post_upgrade_skills: Vec<u8>,
+}
+
+impl StoredUpgradeRecord {
+    /// Returns StdResult<UpgradeRecord> from converting a StoredUpgradeRecord to a displayable
+    /// UpgradeRecord
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - a reference to the Api used to convert human and canonical addresses
+    /// * `id` - index of the upgrade in the history
+    pub fn into_humanized<A: Api>(self, api: &A, id: u64) -> StdResult<UpgradeRecord> {
+        Ok(UpgradeRecord {
+            id,
+            owner: api.human_address(&self.owner)?,
+            time: self.time,
+            upgraded: self.upgraded,
+            burned: self.burned,
+            pre_upgrade_skills: self.pre_upgrade_skills,
+            post_upgrade_skills: self.post_upgrade_skills,
+        })
+    }
+}
+
+/// Returns StdResult<()> after saving a record to a history and appending its index to the
+/// owner's list
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `history_prefix` - prefix of the history storage
+/// * `ids_prefix` - prefix of the storage of each address' indices
+/// * `id` - index of the record
+/// * `owner` - a reference to the owner of the record
+/// * `record` - a reference to the record to save
+pub fn append_record<S: Storage, T: Serialize>(
+    storage: &mut S,
+    history_prefix: &[u8],
+    ids_prefix: &[u8],
+    id: u64,
+    owner: &CanonicalAddr,
+    record: &T,
+) -> StdResult<()> {
+    let mut his_store = PrefixedStorage::new(history_prefix, storage);
+    save(&mut his_store, &id.to_le_bytes(), record)?;
+    let mut store = PrefixedStorage::multilevel(&[ids_prefix, owner.as_slice()], storage);
+    let mut store = AppendStoreMut::attach_or_create(&mut store)?;
+    store.push(&id)
+}
+
+/// a promo code for a free pack
+#[derive(Serialize, Deserialize)]
+pub struct StoredPromoCode {
+    /// sha256 hash of the code
+    pub hash: Vec<u8>,
+    /// optional name of the pack tier the code mints
+    pub tier: Option<String>,
+    /// number of times the code can be redeemed
+    pub uses: u32,
+    /// number of times the code has been redeemed
+    pub redeemed: u32,
+    /// optional number of seconds since epoch time 01/01/1970 after which the code expires
+    pub expires: Option<u64>,
+}
+
+/// the parents of a fused hero
+#[derive(Serialize, Deserialize)]
+pub struct StoredLineage {
+    /// heroes that were fused to create this one
+    pub parents: Vec<HeroInfo>,
+    /// number of fusions in the hero's longest line of ancestors
+    pub generation: u32,
+}
+
+/// Returns StdResult<()> after saving a fused hero's lineage
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `contract` - a reference to the hero's card contract address
+/// * `token_id` - the hero's token ID
+/// * `lineage` - a reference to the hero's lineage
+pub fn save_lineage<S: Storage>(
+    storage: &mut S,
+    contract: &CanonicalAddr,
+    token_id: &str,
+    lineage: &StoredLineage,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::multilevel(&[PREFIX_LINEAGE, contract.as_slice()], storage);
+    save(&mut store, token_id.as_bytes(), lineage)
+}
+
+/// Returns StdResult<Option<StoredLineage>> of a hero's lineage.  Returns Ok(None) if the hero
+/// was not created by fusion
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `contract` - a reference to the hero's card contract address
+/// * `token_id` - the hero's token ID
+pub fn may_load_lineage<S: ReadonlyStorage>(
+    storage: &S,
+    contract: &CanonicalAddr,
+    token_id: &str,
+) -> StdResult<Option<StoredLineage>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_LINEAGE, contract.as_slice()], storage);
+    may_load(&store, token_id.as_bytes())
+}
+
+/// Returns StdResult<()> after saving the token a legacy hero was migrated to
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `contract` - a reference to the legacy card contract address
+/// * `token_id` - the legacy token ID
+/// * `migrated_to` - a reference to the hero's new token
+pub fn save_migration<S: Storage>(
+    storage: &mut S,
+    contract: &CanonicalAddr,
+    token_id: &str,
+    migrated_to: &HeroInfo,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::multilevel(&[PREFIX_MIGRATIONS, contract.as_slice()], storage);
+    save(&mut store, token_id.as_bytes(), migrated_to)
+}
+
+/// Returns StdResult<Option<HeroInfo>> of the token a legacy hero was migrated to.  Returns
+/// Ok(None) if the hero was not migrated
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `contract` - a reference to the legacy card contract address
+/// * `token_id` - the legacy token ID
+pub fn may_load_migration<S: ReadonlyStorage>(
+    storage: &S,
+    contract: &CanonicalAddr,
+    token_id: &str,
+) -> StdResult<Option<HeroInfo>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_MIGRATIONS, contract.as_slice()], storage);
+    may_load(&store, token_id.as_bytes())
+}
+
+/// Returns StdResult<Vec<(u64, T)>> of an address' records and their indices, newest first
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `history_prefix` - prefix of the history storage
+/// * `ids_prefix` - prefix of the storage of each address' indices
+/// * `address` - a reference to the address whose records to display
+/// * `page` - page to start displaying
+/// * `page_size` - number of records per page
+pub fn get_records<S: ReadonlyStorage, T: DeserializeOwned>(
+    storage: &S,
+    history_prefix: &[u8],
+    ids_prefix: &[u8],
+    address: &CanonicalAddr,
+    page: u32,
+    page_size: u32,
+) -> StdResult<Vec<(u64, T)>> {
+    let id_store = ReadonlyPrefixedStorage::multilevel(&[ids_prefix, address.as_slice()], storage);
+    // if the address has no records yet, return an empty list
+    let id_store = if let Some(result) = AppendStore::<u64, _>::attach(&id_store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    let his_store = ReadonlyPrefixedStorage::new(history_prefix, storage);
+    id_store
+        .iter()
+        .rev()
+        .skip((page * page_size) as usize)
+        .take(page_size as usize)
+        .map(|id| id.and_then(|id| load(&his_store, &id.to_le_bytes()).map(|r| (id, r))))
+        .collect()
+}
+
+/// a referrer's code and earnings
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredReferrer {
+    /// the referrer's code
+    pub code: String,
+    /// number of packs bought with the referrer's code
+    pub packs: u32,
+    /// rewards earned in each currency
+    pub rewards: Vec<StoredReward>,
+}
+
+/// a referrer's rewards in one currency
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredReward {
+    /// native coin denomination.  None if the rewards are in a SNIP-20 token
+    pub denom: Option<String>,
+    /// SNIP-20 token contract.  None if the rewards are in a native coin
+    pub token: Option<StoreContractInfo>,
+    /// total amount earned
+    pub earned: Uint128,
+    /// amount accrued that has not been claimed
+    pub unclaimed: Uint128,
+}
+
+impl StoredReward {
+    /// Returns StdResult<ReferralReward> from creating a displayable ReferralReward from a
+    /// StoredReward
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - a reference to the Api used to convert human and canonical addresses
+    pub fn get_humanized<A: Api>(&self, api: &A) -> StdResult<ReferralReward> {
+        let token = if let Some(tkn) = self.token.as_ref() {
+            Some(api.human_address(&tkn.address)?)
+        } else {
+            None
+        };
+        Ok(ReferralReward {
+            denom: self.denom.clone(),
+            token,
+            earned: self.earned,
+            unclaimed: self.unclaimed,
+        })
+    }
+}
+
+/// a recipient of pack sale revenue
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoreRevenueShare {
+    /// recipient's address
+    pub address: CanonicalAddr,
+    /// recipient's share in basis points
+    pub share: u16,
+}
+
+impl StoreRevenueShare {
+    /// Returns StdResult<RevenueShare> from converting a StoreRevenueShare to a displayable
+    /// RevenueShare
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - a reference to the Api used to convert human and canonical addresses
+    pub fn into_humanized<A: Api>(self, api: &A) -> StdResult<RevenueShare> {
+        Ok(RevenueShare {
+            address: api.human_address(&self.address)?,
+            share: self.share,
+        })
+    }
 }
 
 pub fn save<T: Serialize, S: Storage>(storage: &mut S, key: &[u8], value: &T) -> StdResult<()> {
@@ -58,3 +574,31 @@ pub fn may_load<T: DeserializeOwned, S: ReadonlyStorage>(
         None => Ok(None),
     }
 }
+
+// Json is used for items with enums because bincode2 uses a float op when deserializing an enum
+pub fn json_save<T: Serialize, S: Storage>(
+    storage: &mut S,
+    key: &[u8],
+    value: &T,
+) -> StdResult<()> {
+    storage.set(key, &Json::serialize(value)?);
+    Ok(())
+}
+
+pub fn json_load<T: DeserializeOwned, S: ReadonlyStorage>(storage: &S, key: &[u8]) -> StdResult<T> {
+    Json::deserialize(
+        &storage
+            .get(key)
+            .ok_or_else(|| StdError::not_found(type_name::<T>()))?,
+    )
+}
+
+pub fn json_may_load<T: DeserializeOwned, S: ReadonlyStorage>(
+    storage: &S,
+    key: &[u8],
+) -> StdResult<Option<T>> {
+    match storage.get(key) {
+        Some(value) => Json::deserialize(&value).map(Some),
+        None => Ok(None),
+    }
+}
@@ -1,11 +1,35 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// highest value a skill can have before any levels are gained
+pub const BASE_SKILL_CAP: u8 = 100;
+/// amount the skill cap rises with each level
+pub const CAP_PER_LEVEL: u8 = 5;
+/// highest level a card can reach
+pub const MAX_LEVEL: u8 = 20;
+
 /// card stats
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Stats {
-    /// the card's skills at time of minting
+    /// the card's skills at time of minting plus any level bonuses
     pub base: Vec<u8>,
     /// the card's current skills
     pub current: Vec<u8>,
+    /// the card's element.  Cards minted before elements were introduced do not have one.
+    /// This is never skipped when serializing because the arena also stores Stats with bincode
+    #[serde(default)]
+    pub element: Option<String>,
+    /// experience earned in arena battles
+    #[serde(default)]
+    pub xp: u32,
+    /// the card's level
+    #[serde(default)]
+    pub level: u8,
+}
+
+impl Stats {
+    /// Returns u8 of the highest value a skill can reach at the card's level
+    pub fn skill_cap(&self) -> u8 {
+        BASE_SKILL_CAP + CAP_PER_LEVEL * self.level.min(MAX_LEVEL)
+    }
 }
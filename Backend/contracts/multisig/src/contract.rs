@@ -5,15 +5,29 @@ use cosmwasm_std::{
 };
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 
-use secret_toolkit::utils::{pad_handle_result, pad_query_result};
+use secret_toolkit::{
+    snip20::{balance_query, set_viewing_key_msg, transfer_msg},
+    snip721::{
+        register_receive_nft_msg, send_nft_msg, set_viewing_key_msg as set_nft_viewing_key_msg,
+        tokens_query, transfer_nft_msg,
+    },
+    utils::{pad_handle_result, pad_query_result},
+};
 
-use crate::msg::{Action, HandleAnswer, HandleMsg, InitMsg, QueryAnswer, QueryMsg, Status, Vote};
+use crate::ledger::{AssetTotal, StoredAssetTotal, StoredLedgerEntry, TxType};
+use crate::msg::{
+    Action, HandleAnswer, HandleMsg, HeldNfts, InitMsg, QueryAnswer, QueryMsg, QueryWithPermit,
+    Schedule, Snip20Balance, Status, Vote,
+};
+use crate::permit::{revoke_permit, validate_permit, Permission, Permit};
 use crate::proposal::{Proposal, StoredAction, StoredProposal};
 use crate::rand::sha_256;
 use crate::state::{
-    get_proposals, json_may_load, json_save, load, may_load, save, Config, CONFIG_KEY,
-    PREFIX_PROPOSAL, PREFIX_VIEW_KEY, PRNG_KEY,
+    append_ledger, get_ledger, get_proposals, json_may_load, json_save, load, may_load, save,
+    Config, CONFIG_KEY, DEFAULT_VOTING_PERIOD, PREFIX_PROPOSAL, PREFIX_STREAM, PREFIX_VIEW_KEY,
+    PRNG_KEY, TOTALS_KEY,
 };
+use crate::stream::{StoredStream, Stream};
 use crate::viewing_key::{ViewingKey, VIEWING_KEY_SIZE};
 
 pub const BLOCK_SIZE: usize = 256;
@@ -33,13 +47,31 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: InitMsg,
 ) -> InitResult {
-    let prng_seed: Vec<u8> = sha_256(base64::encode(msg.entropy).as_bytes()).to_vec();
+    let prng_seed: Vec<u8> = sha_256(base64::encode(msg.entropy.clone()).as_bytes()).to_vec();
     save(&mut deps.storage, PRNG_KEY, &prng_seed)?;
+    let viewing_key = ViewingKey::new(&env, &prng_seed, msg.entropy.as_ref());
     let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let voting_period = msg.voting_period.unwrap_or(DEFAULT_VOTING_PERIOD);
+    let timelock = msg.timelock.unwrap_or(0);
+    if voting_period == 0 {
+        return Err(StdError::generic_err(
+            "The voting period must be at least one second",
+        ));
+    }
     let config = Config {
         voters: vec![sender_raw],
         count: 0,
         open: Vec::new(),
+        queued: Vec::new(),
+        voting_period,
+        timelock,
+        my_address: deps.api.canonical_address(&env.contract.address)?,
+        tokens: Vec::new(),
+        viewing_key: format!("{}", viewing_key),
+        stream_count: 0,
+        streams: Vec::new(),
+        ledger_count: 0,
+        nft_contracts: Vec::new(),
     };
     save(&mut deps.storage, CONFIG_KEY, &config)?;
     Ok(InitResponse::default())
@@ -60,12 +92,19 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 ) -> HandleResult {
     let response = match msg {
         HandleMsg::Propose {
-            action,
+            actions,
             description,
-        } => try_propose(deps, env, action, description),
+        } => try_propose(deps, env, actions, description),
         HandleMsg::Vote { id, vote } => try_vote(deps, env, id, vote),
+        HandleMsg::Cancel { id } => try_cancel(deps, env, id),
+        HandleMsg::ExecuteProposal { id } => try_execute(deps, env, id),
+        HandleMsg::BatchReceiveNft { .. } => try_receive_nft(deps, env),
+        HandleMsg::Deposit { memo } => try_deposit(deps, env, memo),
+        HandleMsg::Claim { id, amount } => try_claim(deps, env, id, amount),
         HandleMsg::CreateViewingKey { entropy } => try_create_key(deps, env, &entropy),
         HandleMsg::SetViewingKey { key, .. } => try_set_key(deps, env, key),
+        HandleMsg::ExpireProposals {} => try_expire_proposals(deps, env),
+        HandleMsg::RevokePermit { permit_name, .. } => try_revoke_permit(deps, env, &permit_name),
     };
     pad_handle_result(response, BLOCK_SIZE)
 }
@@ -78,12 +117,12 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 ///
 /// * `deps` - mutable reference to Extern containing all the contract's external dependencies
 /// * `env` - Env of contract's environment
-/// * `action` - the Action the proposal will execute if it passes
+/// * `actions` - list of Actions the proposal will execute if it passes
 /// * `description` - optional String to supply additional info
 pub fn try_propose<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    action: Action,
+    actions: Vec<Action>,
     description: Option<String>,
 ) -> HandleResult {
     let sender_raw = deps.api.canonical_address(&env.message.sender)?;
@@ -93,45 +132,192 @@ pub fn try_propose<S: Storage, A: Api, Q: Querier>(
             "Only authorized voters can create a proposal",
         ));
     }
-    let action = match action {
-        Action::AddVoter { address } => {
-            let address_raw = deps.api.canonical_address(&address)?;
-            if config.voters.contains(&address_raw) {
-                return Err(StdError::generic_err(format!(
-                    "{} is already an authorized voter",
-                    address
-                )));
+    if actions.is_empty() {
+        return Err(StdError::generic_err(
+            "A proposal must include at least one action",
+        ));
+    }
+    // track the voter list as each action would change it, so that every action is
+    // validated against the ones before it
+    let mut voters = config.voters.clone();
+    let mut tokens: Vec<CanonicalAddr> = config.tokens.iter().map(|t| t.address.clone()).collect();
+    let mut streams = config.streams.clone();
+    let mut nft_contracts: Vec<CanonicalAddr> = config
+        .nft_contracts
+        .iter()
+        .map(|c| c.address.clone())
+        .collect();
+    let mut stored_actions: Vec<StoredAction> = Vec::new();
+    for action in actions.into_iter() {
+        let stored = match action {
+            Action::AddVoter { address } => {
+                let address_raw = deps.api.canonical_address(&address)?;
+                if voters.contains(&address_raw) {
+                    return Err(StdError::generic_err(format!(
+                        "{} is already an authorized voter",
+                        address
+                    )));
+                }
+                voters.push(address_raw.clone());
+                StoredAction::AddVoter {
+                    address: address_raw,
+                }
             }
-            StoredAction::AddVoter {
-                address: address_raw,
+            Action::RemoveVoter { address } => {
+                let address_raw = deps.api.canonical_address(&address)?;
+                let pos = voters
+                    .iter()
+                    .position(|a| *a == address_raw)
+                    .ok_or_else(|| {
+                        StdError::generic_err(format!(
+                            "{} is not currently an authorized voter",
+                            address
+                        ))
+                    })?;
+                voters.swap_remove(pos);
+                StoredAction::RemoveVoter {
+                    address: address_raw,
+                }
             }
-        }
-        Action::RemoveVoter { address } => {
-            let address_raw = deps.api.canonical_address(&address)?;
-            if !config.voters.contains(&address_raw) {
-                return Err(StdError::generic_err(format!(
-                    "{} is not currently an authorized voter",
-                    address
-                )));
+            Action::Withdraw {
+                recipient,
+                amount,
+                denom,
+            } => {
+                let recipient_raw = deps.api.canonical_address(&recipient)?;
+                StoredAction::Withdraw {
+                    recipient: recipient_raw,
+                    amount,
+                    denom: denom.unwrap_or_else(|| "uscrt".to_string()),
+                }
             }
-            StoredAction::RemoveVoter {
-                address: address_raw,
+            Action::RegisterSnip20 { token } => {
+                let stored = token.get_store(&deps.api)?;
+                if tokens.contains(&stored.address) {
+                    return Err(StdError::generic_err(format!(
+                        "{} is already a registered SNIP-20 contract",
+                        token.address
+                    )));
+                }
+                tokens.push(stored.address.clone());
+                StoredAction::RegisterSnip20 { token: stored }
             }
-        }
-        Action::Withdraw { recipient, amount } => {
-            let recipient_raw = deps.api.canonical_address(&recipient)?;
-            StoredAction::Withdraw {
-                recipient: recipient_raw,
+            Action::WithdrawSnip20 {
+                token,
+                recipient,
                 amount,
+            } => {
+                let token_raw = deps.api.canonical_address(&token)?;
+                // the multisig needs its viewing key set before it can check the balance
+                if !config.tokens.iter().any(|t| t.address == token_raw) {
+                    return Err(StdError::generic_err(format!(
+                        "{} must be registered before its tokens can be withdrawn",
+                        token
+                    )));
+                }
+                StoredAction::WithdrawSnip20 {
+                    token: token_raw,
+                    recipient: deps.api.canonical_address(&recipient)?,
+                    amount,
+                }
             }
-        }
-    };
+            Action::CreateStream {
+                recipient,
+                denom,
+                schedule,
+            } => {
+                match &schedule {
+                    Schedule::Allowance {
+                        amount,
+                        period,
+                        periods,
+                    } => {
+                        if *period == 0 || amount.u128() == 0 || *periods == Some(0) {
+                            return Err(StdError::generic_err(
+                                "An allowance must have a non-zero amount, period and periods",
+                            ));
+                        }
+                        if amount
+                            .u128()
+                            .checked_mul(periods.unwrap_or(1) as u128)
+                            .is_none()
+                        {
+                            return Err(StdError::generic_err(
+                                "An allowance's total amount is larger than the maximum possible",
+                            ));
+                        }
+                    }
+                    Schedule::Vesting { amount, start, end } => {
+                        if end <= start || amount.u128() == 0 {
+                            return Err(StdError::generic_err(
+                                "Vesting needs a non-zero amount and an end after its start",
+                            ));
+                        }
+                    }
+                }
+                StoredAction::CreateStream {
+                    recipient: deps.api.canonical_address(&recipient)?,
+                    denom: denom.unwrap_or_else(|| "uscrt".to_string()),
+                    schedule,
+                }
+            }
+            Action::CancelStream { id } => {
+                let pos = streams.iter().position(|s| *s == id).ok_or_else(|| {
+                    StdError::generic_err(format!("Stream ID {} is not active", id))
+                })?;
+                streams.swap_remove(pos);
+                StoredAction::CancelStream { id }
+            }
+            Action::RegisterNftContract { contract } => {
+                let stored = contract.get_store(&deps.api)?;
+                if nft_contracts.contains(&stored.address) {
+                    return Err(StdError::generic_err(format!(
+                        "{} is already a registered SNIP-721 contract",
+                        contract.address
+                    )));
+                }
+                nft_contracts.push(stored.address.clone());
+                StoredAction::RegisterNftContract { contract: stored }
+            }
+            Action::TransferNft {
+                contract,
+                token_id,
+                recipient,
+            } => StoredAction::TransferNft {
+                contract: registered_nft_contract(&deps.api, &nft_contracts, &contract)?,
+                token_id,
+                recipient: deps.api.canonical_address(&recipient)?,
+            },
+            Action::SendNft {
+                contract,
+                token_id,
+                recipient,
+                msg,
+            } => StoredAction::SendNft {
+                contract: registered_nft_contract(&deps.api, &nft_contracts, &contract)?,
+                token_id,
+                recipient: deps.api.canonical_address(&recipient)?,
+                msg,
+            },
+        };
+        stored_actions.push(stored);
+    }
+    if voters.is_empty() {
+        return Err(StdError::generic_err(
+            "A proposal can not remove every authorized voter",
+        ));
+    }
     let mut proposal = StoredProposal {
         id: config.count,
-        action,
+        proposer: sender_raw.clone(),
+        actions: stored_actions,
         status: Status::Open,
         description,
-        votes: [Vec::new(), Vec::new()],
+        created: env.block.time,
+        expires: env.block.time + config.voting_period,
+        executable_after: None,
+        execute_by: None,
+        votes: [Vec::new(), Vec::new(), Vec::new()],
     };
     config.open.push(config.count);
     config.count += 1;
@@ -139,14 +325,17 @@ pub fn try_propose<S: Storage, A: Api, Q: Querier>(
         config,
         failed: Vec::new(),
         passed: Vec::new(),
+        expired: Vec::new(),
+        queued: Vec::new(),
         messages: Vec::new(),
-        reserve: None,
-        withdrawn: Uint128(0),
+        reserves: Vec::new(),
     };
-    let (_it_closed, rmv_voter) =
+    // clean out any stale proposals while we are here
+    expire_proposals(deps, &env, &mut state)?;
+    let (_it_closed, rmv_voters) =
         process_vote(deps, &env, sender_raw, &mut state, &mut proposal, Vote::Yes)?;
-    if let Some(remove) = rmv_voter {
-        remove_voter(deps, &env, &remove, &mut state)?;
+    if !rmv_voters.is_empty() {
+        remove_voters(deps, &env, &rmv_voters, &mut state)?;
     }
     save(&mut deps.storage, CONFIG_KEY, &state.config)?;
     Ok(HandleResponse {
@@ -155,10 +344,34 @@ pub fn try_propose<S: Storage, A: Api, Q: Querier>(
         data: Some(to_binary(&HandleAnswer::ClosedProposals {
             passed: state.passed,
             failed: state.failed,
+            expired: state.expired,
+            queued: state.queued,
         })?),
     })
 }
 
+/// Returns StdResult<CanonicalAddr> of a SNIP-721 contract after verifying it is registered
+///
+/// # Arguments
+///
+/// * `api` - a reference to the Api used to convert human and canonical addresses
+/// * `registered` - list of registered SNIP-721 contract addresses
+/// * `contract` - a reference to the address of the SNIP-721 contract
+fn registered_nft_contract<A: Api>(
+    api: &A,
+    registered: &[CanonicalAddr],
+    contract: &HumanAddr,
+) -> StdResult<CanonicalAddr> {
+    let contract_raw = api.canonical_address(contract)?;
+    if !registered.contains(&contract_raw) {
+        return Err(StdError::generic_err(format!(
+            "{} must be registered before its NFTs can be moved",
+            contract
+        )));
+    }
+    Ok(contract_raw)
+}
+
 /// Returns HandleResult
 ///
 /// vote on a proposal
@@ -180,23 +393,34 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
     if !config.voters.contains(&sender_raw) {
         return Err(StdError::generic_err("Only authorized voters can vote"));
     }
+    let mut state = State {
+        config,
+        failed: Vec::new(),
+        passed: Vec::new(),
+        expired: Vec::new(),
+        queued: Vec::new(),
+        messages: Vec::new(),
+        reserves: Vec::new(),
+    };
+    // expire stale proposals first, so a vote on one that just expired records the expiration
+    // instead of failing the tx
+    expire_proposals(deps, &env, &mut state)?;
+    let mut save_config = !state.expired.is_empty();
     let prop_store = ReadonlyPrefixedStorage::new(PREFIX_PROPOSAL, &deps.storage);
     let may_prop: Option<StoredProposal> = json_may_load(&prop_store, &id.to_le_bytes())?;
     if let Some(mut proposal) = may_prop {
-        let mut state = State {
-            config,
-            failed: Vec::new(),
-            passed: Vec::new(),
-            messages: Vec::new(),
-            reserve: None,
-            withdrawn: Uint128(0),
-        };
-        let (it_closed, rmv_voter) =
-            process_vote(deps, &env, sender_raw, &mut state, &mut proposal, vote)?;
-        if it_closed {
-            if let Some(remove) = rmv_voter {
-                remove_voter(deps, &env, &remove, &mut state)?;
+        // the vote is not counted if the proposal just expired
+        if !state.expired.contains(&id) {
+            let (it_closed, rmv_voters) =
+                process_vote(deps, &env, sender_raw, &mut state, &mut proposal, vote)?;
+            if it_closed {
+                if !rmv_voters.is_empty() {
+                    remove_voters(deps, &env, &rmv_voters, &mut state)?;
+                }
+                save_config = true;
             }
+        }
+        if save_config {
             save(&mut deps.storage, CONFIG_KEY, &state.config)?;
         }
         return Ok(HandleResponse {
@@ -205,6 +429,8 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
             data: Some(to_binary(&HandleAnswer::ClosedProposals {
                 passed: state.passed,
                 failed: state.failed,
+                expired: state.expired,
+                queued: state.queued,
             })?),
         });
     }
@@ -214,6 +440,347 @@ pub fn try_vote<S: Storage, A: Api, Q: Querier>(
     )))
 }
 
+/// Returns HandleResult
+///
+/// execute a passed proposal once its timelock has ended
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `id` - ID of the proposal being executed
+pub fn try_execute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u32,
+) -> HandleResult {
+    let prop_store = ReadonlyPrefixedStorage::new(PREFIX_PROPOSAL, &deps.storage);
+    let mut proposal: StoredProposal = json_may_load(&prop_store, &id.to_le_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Proposal ID {} is not valid", id)))?;
+    if proposal.status != Status::Queued {
+        return Err(StdError::generic_err(format!(
+            "Proposal {} is not waiting to be executed",
+            id
+        )));
+    }
+    if let Some(after) = proposal.executable_after {
+        if env.block.time < after {
+            return Err(StdError::generic_err(format!(
+                "Proposal {} can not be executed until {}",
+                id, after
+            )));
+        }
+    }
+    if proposal.missed_execution(env.block.time) {
+        return Err(StdError::generic_err(format!(
+            "Proposal {} was not executed in time and has expired",
+            id
+        )));
+    }
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let mut state = State {
+        config,
+        failed: Vec::new(),
+        passed: Vec::new(),
+        expired: Vec::new(),
+        queued: Vec::new(),
+        messages: Vec::new(),
+        reserves: Vec::new(),
+    };
+    let (executed, rmv_voters) = execute_actions(deps, &env, &proposal, &mut state)?;
+    if !executed {
+        return Err(StdError::generic_err(format!(
+            "Proposal {} can not be executed because the reserve can not cover its withdrawals",
+            id
+        )));
+    }
+    proposal.status = Status::Passed;
+    proposal.executable_after = None;
+    proposal.execute_by = None;
+    state.passed.push(id);
+    state.config.queued.retain(|i| *i != id);
+    let mut prop_store = PrefixedStorage::new(PREFIX_PROPOSAL, &mut deps.storage);
+    json_save(&mut prop_store, &id.to_le_bytes(), &proposal)?;
+    if !rmv_voters.is_empty() {
+        remove_voters(deps, &env, &rmv_voters, &mut state)?;
+    }
+    save(&mut deps.storage, CONFIG_KEY, &state.config)?;
+    Ok(HandleResponse {
+        messages: state.messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClosedProposals {
+            passed: state.passed,
+            failed: state.failed,
+            expired: state.expired,
+            queued: state.queued,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// cancel an open or queued proposal
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `id` - ID of the proposal being cancelled
+pub fn try_cancel<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u32,
+) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut prop_store = PrefixedStorage::new(PREFIX_PROPOSAL, &mut deps.storage);
+    let mut proposal: StoredProposal = json_may_load(&prop_store, &id.to_le_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Proposal ID {} is not valid", id)))?;
+    if proposal.proposer != sender_raw {
+        return Err(StdError::generic_err(
+            "Only the proposer can cancel a proposal",
+        ));
+    }
+    if proposal.status != Status::Open && proposal.status != Status::Queued {
+        return Err(StdError::generic_err(
+            "You can not cancel a proposal that has already closed",
+        ));
+    }
+    // an expired proposal must be recorded as expired, not cancelled
+    if (proposal.status == Status::Open && proposal.is_expired(env.block.time))
+        || proposal.missed_execution(env.block.time)
+    {
+        return Err(StdError::generic_err(format!(
+            "Proposal {} has expired and can not be cancelled",
+            id
+        )));
+    }
+    proposal.status = Status::Cancelled;
+    proposal.executable_after = None;
+    proposal.execute_by = None;
+    json_save(&mut prop_store, &id.to_le_bytes(), &proposal)?;
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    // remove proposal from the lists of open and queued proposals
+    let count = config.open.len() + config.queued.len();
+    config.open.retain(|i| *i != id);
+    config.queued.retain(|i| *i != id);
+    if config.open.len() + config.queued.len() != count {
+        save(&mut deps.storage, CONFIG_KEY, &config)?;
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Cancel { id })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// accept NFTs sent from registered SNIP-721 contracts
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+pub fn try_receive_nft<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if !config.nft_contracts.iter().any(|c| c.address == sender_raw) {
+        return Err(StdError::generic_err(format!(
+            "{} is not a registered SNIP-721 contract",
+            env.message.sender
+        )));
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: None,
+    })
+}
+
+/// Returns HandleResult
+///
+/// record the native coins sent with this message as a deposit to the treasury
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `memo` - optional String describing the deposit
+pub fn try_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    memo: Option<String>,
+) -> HandleResult {
+    let amount: Vec<Coin> = env
+        .message
+        .sent_funds
+        .into_iter()
+        .filter(|c| c.amount.u128() > 0)
+        .collect();
+    if amount.is_empty() {
+        return Err(StdError::generic_err("No funds were sent with the deposit"));
+    }
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    for coin in amount.iter() {
+        append_ledger(
+            &mut deps.storage,
+            &mut config,
+            StoredLedgerEntry {
+                id: 0,
+                tx_type: TxType::Deposit,
+                proposal: None,
+                stream: None,
+                address: sender_raw.clone(),
+                amount: coin.amount,
+                denom: Some(coin.denom.clone()),
+                token: None,
+                time: env.block.time,
+                memo: memo.clone(),
+            },
+        )?;
+    }
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Deposit { amount })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// claim funds from a stream
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `id` - ID of the stream being claimed from
+/// * `amount` - optional amount to claim.  Defaults to everything currently claimable
+pub fn try_claim<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u32,
+    amount: Option<Uint128>,
+) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if !config.streams.contains(&id) {
+        return Err(StdError::generic_err(format!(
+            "Stream ID {} is not active",
+            id
+        )));
+    }
+    let mut stream_store = PrefixedStorage::new(PREFIX_STREAM, &mut deps.storage);
+    let mut stream: StoredStream = json_may_load(&stream_store, &id.to_le_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Stream ID {} is not valid", id)))?;
+    if stream.recipient != sender_raw {
+        return Err(StdError::generic_err(
+            "Only the recipient can claim from a stream",
+        ));
+    }
+    let claimable = stream.claimable(env.block.time);
+    let amount = amount.unwrap_or(claimable);
+    if amount.u128() == 0 {
+        return Err(StdError::generic_err("There is nothing to claim"));
+    }
+    if amount > claimable {
+        return Err(StdError::generic_err(format!(
+            "You can only claim up to {} {} right now",
+            claimable, stream.denom
+        )));
+    }
+    stream.claim(amount, env.block.time);
+    json_save(&mut stream_store, &id.to_le_bytes(), &stream)?;
+    append_ledger(
+        &mut deps.storage,
+        &mut config,
+        StoredLedgerEntry {
+            id: 0,
+            tx_type: TxType::Claim,
+            proposal: None,
+            stream: Some(id),
+            address: sender_raw,
+            amount,
+            denom: Some(stream.denom.clone()),
+            token: None,
+            time: env.block.time,
+            memo: None,
+        },
+    )?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &stream.denom)?
+        .amount;
+    if amount > balance {
+        return Err(StdError::generic_err(
+            "The multisig does not have enough funds to pay this claim",
+        ));
+    }
+    if stream.is_finished(env.block.time) {
+        config.streams.retain(|s| *s != id);
+    }
+    save(&mut deps.storage, CONFIG_KEY, &config)?;
+    Ok(HandleResponse {
+        messages: vec![CosmosMsg::Bank(BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: env.message.sender,
+            amount: vec![Coin {
+                denom: stream.denom.clone(),
+                amount,
+            }],
+        })],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Claim {
+            amount,
+            denom: stream.denom,
+        })?),
+    })
+}
+
+/// Returns HandleResult
+///
+/// mark all open proposals whose voting period has ended, and all queued proposals that were
+/// not executed in time, as expired
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+pub fn try_expire_proposals<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let mut state = State {
+        config,
+        failed: Vec::new(),
+        passed: Vec::new(),
+        expired: Vec::new(),
+        queued: Vec::new(),
+        messages: Vec::new(),
+        reserves: Vec::new(),
+    };
+    expire_proposals(deps, &env, &mut state)?;
+    if !state.expired.is_empty() {
+        save(&mut deps.storage, CONFIG_KEY, &state.config)?;
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClosedProposals {
+            passed: state.passed,
+            failed: state.failed,
+            expired: state.expired,
+            queued: state.queued,
+        })?),
+    })
+}
+
 /// Returns HandleResult
 ///
 /// creates a viewing key
@@ -230,9 +797,9 @@ pub fn try_create_key<S: Storage, A: Api, Q: Querier>(
 ) -> HandleResult {
     let sender_raw = &deps.api.canonical_address(&env.message.sender)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
-    if !config.voters.contains(&sender_raw) {
+    if !config.voters.contains(&sender_raw) && !is_recipient(&deps.storage, &config, sender_raw)? {
         return Err(StdError::generic_err(
-            "Only authorized voters need a viewing key",
+            "Only authorized voters and stream recipients need a viewing key",
         ));
     }
     let prng_seed: Vec<u8> = load(&deps.storage, PRNG_KEY)?;
@@ -264,9 +831,9 @@ pub fn try_set_key<S: Storage, A: Api, Q: Querier>(
 ) -> HandleResult {
     let sender_raw = &deps.api.canonical_address(&env.message.sender)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
-    if !config.voters.contains(&sender_raw) {
+    if !config.voters.contains(&sender_raw) && !is_recipient(&deps.storage, &config, sender_raw)? {
         return Err(StdError::generic_err(
-            "Only authorized voters need a viewing key",
+            "Only authorized voters and stream recipients need a viewing key",
         ));
     }
     let vk = ViewingKey(key.clone());
@@ -280,6 +847,31 @@ pub fn try_set_key<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns HandleResult
+///
+/// revoke a query permit
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - Env of contract's environment
+/// * `permit_name` - name of the permit to revoke
+pub fn try_revoke_permit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    permit_name: &str,
+) -> HandleResult {
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    revoke_permit(&mut deps.storage, &sender_raw, permit_name)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RevokePermit {
+            permit_name: permit_name.to_string(),
+        })?),
+    })
+}
+
 /////////////////////////////////////// Query /////////////////////////////////////
 /// Returns QueryResult
 ///
@@ -292,33 +884,115 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
         QueryMsg::ListOpenProposals {
             address,
             viewing_key,
-        } => query_open_proposals(deps, &address, viewing_key),
+            time,
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_open_proposals(deps, &a, time)),
         QueryMsg::ListAllProposals {
             address,
             viewing_key,
             start_at,
             limit,
-        } => query_all_proposals(deps, &address, viewing_key, start_at, limit),
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_all_proposals(deps, &a, start_at, limit)),
         QueryMsg::Proposal {
             id,
             address,
             viewing_key,
-        } => query_proposal(deps, id, &address, viewing_key),
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_proposal(deps, id, &a)),
         QueryMsg::ListVoters {
             address,
             viewing_key,
-        } => query_voters(deps, &address, viewing_key),
-        QueryMsg::Vote {
-            id,
-            voter,
-            viewer,
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_voters(deps, &a)),
+        QueryMsg::Balances {
+            address,
             viewing_key,
-        } => query_vote(deps, id, &voter, viewer.as_ref(), viewing_key),
-    };
-    pad_query_result(response, BLOCK_SIZE)
-}
-
-pub fn query_vote<S: Storage, A: Api, Q: Querier>(
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_balances(deps, &a)),
+        QueryMsg::Ledger {
+            address,
+            viewing_key,
+            start_at,
+            limit,
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_ledger(deps, &a, start_at, limit)),
+        QueryMsg::Treasury {
+            address,
+            viewing_key,
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_treasury(deps, &a)),
+        QueryMsg::HeldNfts {
+            address,
+            viewing_key,
+            contract,
+            start_after,
+            limit,
+        } => check_viewer(deps, &address, viewing_key)
+            .and_then(|a| query_held_nfts(deps, &a, contract.as_ref(), start_after, limit)),
+        QueryMsg::Streams {
+            address,
+            viewing_key,
+            time,
+        } => check_viewer(deps, &address, viewing_key).and_then(|a| query_streams(deps, &a, time)),
+        QueryMsg::Vote {
+            id,
+            voter,
+            viewer,
+            viewing_key,
+        } => query_vote(deps, id, &voter, viewer.as_ref(), viewing_key),
+        QueryMsg::WithPermit { permit, query } => permit_queries(deps, &permit, query),
+    };
+    pad_query_result(response, BLOCK_SIZE)
+}
+
+/// Returns QueryResult from a query authenticated by a permit
+///
+/// # Arguments
+///
+/// * `deps` - reference to Extern containing all the contract's external dependencies
+/// * `permit` - a reference to the querier's Permit
+/// * `query` - the QueryWithPermit to perform
+fn permit_queries<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    permit: &Permit,
+    query: QueryWithPermit,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let my_address = deps.api.human_address(&config.my_address)?;
+    let permission = match query {
+        QueryWithPermit::Balances {}
+        | QueryWithPermit::Ledger { .. }
+        | QueryWithPermit::Treasury {}
+        | QueryWithPermit::HeldNfts { .. }
+        | QueryWithPermit::Streams { .. } => Permission::Treasury,
+        _ => Permission::Proposals,
+    };
+    let address_raw = validate_permit(&deps.storage, permit, &my_address, permission)?;
+    match query {
+        QueryWithPermit::ListAllProposals { start_at, limit } => {
+            query_all_proposals(deps, &address_raw, start_at, limit)
+        }
+        QueryWithPermit::ListOpenProposals { time } => {
+            query_open_proposals(deps, &address_raw, time)
+        }
+        QueryWithPermit::Proposal { id } => query_proposal(deps, id, &address_raw),
+        QueryWithPermit::ListVoters {} => query_voters(deps, &address_raw),
+        QueryWithPermit::Vote { id, voter } => {
+            let voter_raw = deps.api.canonical_address(&voter)?;
+            vote_info(deps, id, &voter_raw, &address_raw)
+        }
+        QueryWithPermit::Balances {} => query_balances(deps, &address_raw),
+        QueryWithPermit::Ledger { start_at, limit } => {
+            query_ledger(deps, &address_raw, start_at, limit)
+        }
+        QueryWithPermit::Treasury {} => query_treasury(deps, &address_raw),
+        QueryWithPermit::HeldNfts {
+            contract,
+            start_after,
+            limit,
+        } => query_held_nfts(deps, &address_raw, contract.as_ref(), start_after, limit),
+        QueryWithPermit::Streams { time } => query_streams(deps, &address_raw, time),
+    }
+}
+
+pub fn query_vote<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     id: u32,
     voter: &HumanAddr,
@@ -326,7 +1000,6 @@ pub fn query_vote<S: Storage, A: Api, Q: Querier>(
     viewing_key: String,
 ) -> QueryResult {
     let mut is_viewer = false;
-    let config: Config = load(&deps.storage, CONFIG_KEY)?;
     let voter_raw = deps.api.canonical_address(voter)?;
     let (viewer_raw, vwr_given) = if let Some(vwr) = viewer {
         (deps.api.canonical_address(vwr)?, true)
@@ -337,28 +1010,44 @@ pub fn query_vote<S: Storage, A: Api, Q: Querier>(
     // if the key matches the viewer address first
     if vwr_given && check_key(&deps.storage, &viewer_raw, viewing_key.clone()).is_ok() {
         is_viewer = true;
-        if !config.voters.contains(&viewer_raw) {
-            return Err(StdError::generic_err(
-                "Only authorized voters may perform this query",
-            ));
-        }
     }
     // check if this is the voter's key if we need to
     if !is_viewer {
         check_key(&deps.storage, &voter_raw, viewing_key)?;
-        if !config.voters.contains(&voter_raw) {
-            return Err(StdError::generic_err(
-                "Only authorized voters may perform this query",
-            ));
-        }
+        return vote_info(deps, id, &voter_raw, &voter_raw);
+    }
+    vote_info(deps, id, &voter_raw, &viewer_raw)
+}
+
+/// Returns QueryResult displaying a voter's vote on a proposal
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `id` - proposal id
+/// * `voter_raw` - a reference to the voter's address
+/// * `viewer_raw` - a reference to the authenticated querier's address
+fn vote_info<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    id: u32,
+    voter_raw: &CanonicalAddr,
+    viewer_raw: &CanonicalAddr,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if !config.voters.contains(viewer_raw) {
+        return Err(StdError::generic_err(
+            "Only authorized voters may perform this query",
+        ));
     }
     let prop_store = ReadonlyPrefixedStorage::new(PREFIX_PROPOSAL, &deps.storage);
     let may_prop: Option<StoredProposal> = json_may_load(&prop_store, &id.to_le_bytes())?;
     if let Some(prop) = may_prop {
-        let vote = if let Some(_a) = prop.votes[0].iter().find(|&a| *a == voter_raw) {
+        let vote = if prop.votes[0].contains(voter_raw) {
             Some(Vote::Yes)
-        } else if let Some(_a) = prop.votes[1].iter().find(|&a| *a == voter_raw) {
+        } else if prop.votes[1].contains(voter_raw) {
             Some(Vote::No)
+        } else if prop.votes[2].contains(voter_raw) {
+            Some(Vote::Abstain)
         } else {
             None
         };
@@ -372,13 +1061,10 @@ pub fn query_vote<S: Storage, A: Api, Q: Querier>(
 
 pub fn query_voters<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    address: &HumanAddr,
-    viewing_key: String,
+    address_raw: &CanonicalAddr,
 ) -> QueryResult {
-    let address_raw = deps.api.canonical_address(address)?;
-    check_key(&deps.storage, &address_raw, viewing_key)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
-    if !config.voters.contains(&address_raw) {
+    if !config.voters.contains(address_raw) {
         return Err(StdError::generic_err(
             "Only authorized voters may perform this query",
         ));
@@ -392,16 +1078,199 @@ pub fn query_voters<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn query_balances<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address_raw: &CanonicalAddr,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if !config.voters.contains(address_raw) {
+        return Err(StdError::generic_err(
+            "Only authorized voters may perform this query",
+        ));
+    }
+    let my_address = deps.api.human_address(&config.my_address)?;
+    let native = deps.querier.query_all_balances(&my_address)?;
+    let snip20 = config
+        .tokens
+        .iter()
+        .map(|t| {
+            let token = deps.api.human_address(&t.address)?;
+            let amount = balance_query(
+                &deps.querier,
+                my_address.clone(),
+                config.viewing_key.clone(),
+                BLOCK_SIZE,
+                t.code_hash.clone(),
+                token.clone(),
+            )?
+            .amount;
+            Ok(Snip20Balance { token, amount })
+        })
+        .collect::<StdResult<Vec<Snip20Balance>>>()?;
+    to_binary(&QueryAnswer::Balances { native, snip20 })
+}
+
+pub fn query_ledger<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address_raw: &CanonicalAddr,
+    start_at: Option<u64>,
+    limit: Option<u32>,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if !config.voters.contains(address_raw) {
+        return Err(StdError::generic_err(
+            "Only authorized voters may perform this query",
+        ));
+    }
+    let entries = if config.ledger_count == 0 {
+        Vec::new()
+    } else {
+        get_ledger(
+            &deps,
+            start_at.unwrap_or(config.ledger_count - 1),
+            limit.unwrap_or(30),
+        )?
+    };
+    to_binary(&QueryAnswer::Ledger {
+        entries,
+        count: config.ledger_count,
+    })
+}
+
+pub fn query_treasury<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address_raw: &CanonicalAddr,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if !config.voters.contains(address_raw) {
+        return Err(StdError::generic_err(
+            "Only authorized voters may perform this query",
+        ));
+    }
+    let my_address = deps.api.human_address(&config.my_address)?;
+    let stored: Vec<StoredAssetTotal> =
+        json_may_load(&deps.storage, TOTALS_KEY)?.unwrap_or_default();
+    let totals = stored
+        .into_iter()
+        .map(|t| {
+            let (token, balance) = if let Some(tkn) = t.token {
+                let code_hash = config
+                    .tokens
+                    .iter()
+                    .find(|c| c.address == tkn)
+                    .map(|c| c.code_hash.clone())
+                    .ok_or_else(|| StdError::generic_err("SNIP-20 token list is corrupt"))?;
+                let token = deps.api.human_address(&tkn)?;
+                let balance = balance_query(
+                    &deps.querier,
+                    my_address.clone(),
+                    config.viewing_key.clone(),
+                    BLOCK_SIZE,
+                    code_hash,
+                    token.clone(),
+                )?
+                .amount;
+                (Some(token), balance)
+            } else {
+                let denom = t.denom.clone().unwrap_or_else(|| "uscrt".to_string());
+                (
+                    None,
+                    deps.querier.query_balance(&my_address, &denom)?.amount,
+                )
+            };
+            Ok(AssetTotal {
+                denom: t.denom,
+                token,
+                deposited: t.deposited,
+                withdrawn: t.withdrawn,
+                balance,
+            })
+        })
+        .collect::<StdResult<Vec<AssetTotal>>>()?;
+    to_binary(&QueryAnswer::Treasury { totals })
+}
+
+pub fn query_held_nfts<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address_raw: &CanonicalAddr,
+    contract: Option<&HumanAddr>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    if !config.voters.contains(address_raw) {
+        return Err(StdError::generic_err(
+            "Only authorized voters may perform this query",
+        ));
+    }
+    // token ids are only ordered within a single contract
+    if start_after.is_some() && contract.is_none() {
+        return Err(StdError::generic_err(
+            "A SNIP-721 contract must be specified to list tokens after a token id",
+        ));
+    }
+    let filter = if let Some(addr) = contract {
+        Some(deps.api.canonical_address(addr)?)
+    } else {
+        None
+    };
+    let my_address = deps.api.human_address(&config.my_address)?;
+    let mut nfts: Vec<HeldNfts> = Vec::new();
+    for nft_contract in config.nft_contracts.iter() {
+        if let Some(fltr) = filter.as_ref() {
+            if nft_contract.address != *fltr {
+                continue;
+            }
+        }
+        let contract_human = deps.api.human_address(&nft_contract.address)?;
+        let token_ids = tokens_query(
+            &deps.querier,
+            my_address.clone(),
+            None,
+            Some(config.viewing_key.clone()),
+            start_after.clone(),
+            Some(limit.unwrap_or(30)),
+            BLOCK_SIZE,
+            nft_contract.code_hash.clone(),
+            contract_human.clone(),
+        )?
+        .tokens;
+        nfts.push(HeldNfts {
+            contract: contract_human,
+            token_ids,
+        });
+    }
+    to_binary(&QueryAnswer::HeldNfts { nfts })
+}
+
+pub fn query_streams<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address_raw: &CanonicalAddr,
+    time: Option<u64>,
+) -> QueryResult {
+    let config: Config = load(&deps.storage, CONFIG_KEY)?;
+    let is_voter = config.voters.contains(address_raw);
+    let stream_store = ReadonlyPrefixedStorage::new(PREFIX_STREAM, &deps.storage);
+    let mut streams: Vec<Stream> = Vec::new();
+    for id in config.streams.iter() {
+        let may_stream: Option<StoredStream> = json_may_load(&stream_store, &id.to_le_bytes())?;
+        if let Some(stream) = may_stream {
+            // non-voters only see their own streams
+            if is_voter || stream.recipient == *address_raw {
+                streams.push(stream.into_humanized(&deps.api, time)?);
+            }
+        }
+    }
+    to_binary(&QueryAnswer::StreamList { streams })
+}
+
 pub fn query_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     id: u32,
-    address: &HumanAddr,
-    viewing_key: String,
+    address_raw: &CanonicalAddr,
 ) -> QueryResult {
-    let address_raw = deps.api.canonical_address(address)?;
-    check_key(&deps.storage, &address_raw, viewing_key)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
-    if !config.voters.contains(&address_raw) {
+    if !config.voters.contains(address_raw) {
         return Err(StdError::generic_err(
             "Only authorized voters may perform this query",
         ));
@@ -421,15 +1290,12 @@ pub fn query_proposal<S: Storage, A: Api, Q: Querier>(
 
 pub fn query_all_proposals<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    address: &HumanAddr,
-    viewing_key: String,
+    address_raw: &CanonicalAddr,
     start_at: Option<u32>,
     limit: Option<u32>,
 ) -> QueryResult {
-    let address_raw = deps.api.canonical_address(address)?;
-    check_key(&deps.storage, &address_raw, viewing_key)?;
     let config: Config = load(&deps.storage, CONFIG_KEY)?;
-    if !config.voters.contains(&address_raw) {
+    if !config.voters.contains(address_raw) {
         return Err(StdError::generic_err(
             "Only authorized voters may perform this query",
         ));
@@ -444,13 +1310,11 @@ pub fn query_all_proposals<S: Storage, A: Api, Q: Querier>(
 
 pub fn query_open_proposals<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
-    address: &HumanAddr,
-    viewing_key: String,
+    address_raw: &CanonicalAddr,
+    time: Option<u64>,
 ) -> QueryResult {
-    let address_raw = deps.api.canonical_address(address)?;
-    check_key(&deps.storage, &address_raw, viewing_key)?;
     let mut config: Config = load(&deps.storage, CONFIG_KEY)?;
-    if !config.voters.contains(&address_raw) {
+    if !config.voters.contains(address_raw) {
         return Err(StdError::generic_err(
             "Only authorized voters may perform this query",
         ));
@@ -461,12 +1325,34 @@ pub fn query_open_proposals<S: Storage, A: Api, Q: Querier>(
     for idx in config.open.iter().rev() {
         let may_prop: Option<StoredProposal> = json_may_load(&prop_store, &idx.to_le_bytes())?;
         if let Some(prop) = may_prop {
+            // queries do not know the block time, so skip proposals that have expired by the
+            // time the querier supplied even if they have not been marked yet
+            if time.map_or(false, |t| prop.is_expired(t)) {
+                continue;
+            }
             proposals.push(prop.into_humanized(&deps.api)?);
         }
     }
     to_binary(&QueryAnswer::ProposalList { proposals })
 }
 
+/// Returns StdResult<CanonicalAddr> of the querier after validating their viewing key
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `address` - a reference to the querier's address
+/// * `viewing_key` - String key used for authentication
+fn check_viewer<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    viewing_key: String,
+) -> StdResult<CanonicalAddr> {
+    let address_raw = deps.api.canonical_address(address)?;
+    check_key(&deps.storage, &address_raw, viewing_key)?;
+    Ok(address_raw)
+}
+
 fn check_key<S: ReadonlyStorage>(
     storage: &S,
     address: &CanonicalAddr,
@@ -486,12 +1372,53 @@ fn check_key<S: ReadonlyStorage>(
     ))
 }
 
+/// Returns StdResult<bool> which is true if the address is the recipient of an active stream
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `config` - a reference to the Config
+/// * `address` - a reference to the address in question
+fn is_recipient<S: ReadonlyStorage>(
+    storage: &S,
+    config: &Config,
+    address: &CanonicalAddr,
+) -> StdResult<bool> {
+    let stream_store = ReadonlyPrefixedStorage::new(PREFIX_STREAM, storage);
+    for id in config.streams.iter() {
+        let may_stream: Option<StoredStream> = json_may_load(&stream_store, &id.to_le_bytes())?;
+        if let Some(stream) = may_stream {
+            if stream.recipient == *address {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
 pub struct State {
     pub config: Config,
     pub passed: Vec<u32>,
     pub failed: Vec<u32>,
+    pub expired: Vec<u32>,
+    pub queued: Vec<u32>,
     pub messages: Vec<CosmosMsg>,
-    pub reserve: Option<Uint128>,
+    pub reserves: Vec<Reserve>,
+}
+
+/// an asset the multisig can withdraw
+#[derive(Clone, PartialEq)]
+pub enum Asset {
+    /// native coin denomination
+    Native(String),
+    /// SNIP-20 token contract address
+    Snip20(CanonicalAddr),
+}
+
+/// an asset's balance and the amount already committed in this tx
+pub struct Reserve {
+    pub asset: Asset,
+    pub balance: Uint128,
     pub withdrawn: Uint128,
 }
 
@@ -502,26 +1429,40 @@ fn process_vote<S: Storage, A: Api, Q: Querier>(
     state: &mut State,
     proposal: &mut StoredProposal,
     vote: Vote,
-) -> StdResult<(bool, Option<CanonicalAddr>)> {
-    if let Status::Open = proposal.status {
-        let idx: usize = if let Vote::Yes = vote { 0 } else { 1 };
-        // if they already voted in the opposite, remove the old vote
-        if let Some(pos) = proposal.votes[1 - idx].iter().position(|a| *a == voter) {
-            proposal.votes[1 - idx].swap_remove(pos);
+) -> StdResult<(bool, Vec<CanonicalAddr>)> {
+    match proposal.status {
+        Status::Open => {
+            if proposal.is_expired(env.block.time) {
+                return Err(StdError::generic_err(format!(
+                    "Proposal {} has expired and can no longer be voted on",
+                    proposal.id
+                )));
+            }
         }
-        // only add vote if the address didn't already vote this way
-        if proposal.votes[idx].contains(&voter) {
-            return Err(StdError::generic_err(format!(
-                "You have already cast the exact same vote for Proposal {}",
-                proposal.id
-            )));
+        // voters may still change their vote to veto a proposal waiting out the timelock
+        Status::Queued => (),
+        _ => {
+            return Err(StdError::generic_err(
+                "You can not vote on a proposal that is no longer open",
+            ))
         }
-        proposal.votes[idx].push(voter);
-        return check_close(deps, env, proposal, true, state);
     }
-    Err(StdError::generic_err(
-        "You can not vote on a proposal that is no longer open",
-    ))
+    let idx: usize = match vote {
+        Vote::Yes => 0,
+        Vote::No => 1,
+        Vote::Abstain => 2,
+    };
+    // only add vote if the address didn't already vote this way
+    if proposal.votes[idx].contains(&voter) {
+        return Err(StdError::generic_err(format!(
+            "You have already cast the exact same vote for Proposal {}",
+            proposal.id
+        )));
+    }
+    // if they already voted differently, remove the old vote
+    remove_vote(&voter, proposal);
+    proposal.votes[idx].push(voter);
+    check_close(deps, env, proposal, true, state)
 }
 
 fn check_close<S: Storage, A: Api, Q: Querier>(
@@ -530,85 +1471,534 @@ fn check_close<S: Storage, A: Api, Q: Querier>(
     proposal: &mut StoredProposal,
     save_prop: bool,
     state: &mut State,
-) -> StdResult<(bool, Option<CanonicalAddr>)> {
+) -> StdResult<(bool, Vec<CanonicalAddr>)> {
     let mut closed_prop = false;
-    let mut rmv_voter: Option<CanonicalAddr> = None;
-    if let Status::Open = proposal.status {
-        let mut save_it = save_prop;
-        // check if both yes or no got enough votes to win
-        for idx in 0..2 {
-            if proposal.votes[idx].len() > state.config.voters.len() / 2 {
-                // if yes won, perform the action
-                if idx == 0 {
-                    match &proposal.action {
-                        StoredAction::AddVoter { address } => {
-                            if !state.config.voters.contains(&address) {
-                                state.config.voters.push(address.clone());
-                            }
-                        }
-                        StoredAction::RemoveVoter { address } => {
-                            if let Some(pos) = state.config.voters.iter().position(|a| a == address)
-                            {
-                                state.config.voters.swap_remove(pos);
-                                rmv_voter = Some(address.clone());
-                            }
-                        }
-                        StoredAction::Withdraw { recipient, amount } => {
-                            let reserve = if state.reserve.is_none() {
-                                let res = deps
-                                    .querier
-                                    .query_balance(&env.contract.address, "uscrt")?
-                                    .amount;
-                                state.reserve = Some(res);
-                                res
-                            } else {
-                                state.reserve.unwrap()
-                            };
-                            if (state.withdrawn + *amount) > reserve {
-                                return Err(StdError::generic_err(
-                                    format!("Withdrawal Proposal {} passed, but there is not enough SCRT in the reserve", proposal.id),
-                                ));
-                            }
-                            state.withdrawn += *amount;
-                            let withdrawal_coins: Vec<Coin> = vec![Coin {
-                                denom: "uscrt".to_string(),
-                                amount: *amount,
-                            }];
-                            state.messages.push(CosmosMsg::Bank(BankMsg::Send {
-                                from_address: env.contract.address.clone(),
-                                to_address: deps.api.human_address(&recipient)?,
-                                amount: withdrawal_coins,
-                            }));
+    let mut rmv_voters: Vec<CanonicalAddr> = Vec::new();
+    match proposal.status {
+        Status::Open => {
+            // an expired proposal can not pass or fail
+            if proposal.is_expired(env.block.time) {
+                proposal.status = Status::Expired;
+                state.expired.push(proposal.id);
+                closed_prop = true;
+            } else {
+                // abstentions count toward quorum but not the threshold, so once more than
+                // half of the voters have voted, a side wins with a majority of the voters
+                // that did not abstain
+                let quorum = has_quorum(proposal, &state.config.voters);
+                let deciding = state
+                    .config
+                    .voters
+                    .len()
+                    .saturating_sub(proposal.votes[2].len());
+                // if yes won, perform the action or queue it behind the timelock
+                if quorum && 2 * proposal.votes[0].len() > deciding {
+                    if state.config.timelock > 0 {
+                        queue_proposal(proposal, env.block.time + state.config.timelock, state);
+                    } else {
+                        let (executed, rmv) = execute_actions(deps, env, proposal, state)?;
+                        if executed {
+                            proposal.status = Status::Passed;
+                            state.passed.push(proposal.id);
+                            rmv_voters = rmv;
+                        } else {
+                            // can not afford it yet, so let anyone execute it once funded
+                            queue_proposal(proposal, env.block.time, state);
                         }
                     }
-                    proposal.status = Status::Passed;
-                    state.passed.push(proposal.id);
+                    closed_prop = true;
                 // no won, so mark it as failed
-                } else {
+                } else if quorum && 2 * proposal.votes[1].len() > deciding {
                     proposal.status = Status::Failed;
                     state.failed.push(proposal.id);
+                    closed_prop = true;
                 }
+            }
+            if closed_prop {
                 // remove proposal from list of open proposals
                 if let Some(pos) = state.config.open.iter().position(|i| *i == proposal.id) {
                     state.config.open.swap_remove(pos);
                 }
+            }
+        }
+        Status::Queued if proposal.missed_execution(env.block.time) => {
+            proposal.status = Status::Expired;
+            proposal.executable_after = None;
+            proposal.execute_by = None;
+            state.expired.push(proposal.id);
+            state.config.queued.retain(|i| *i != proposal.id);
+            closed_prop = true;
+        }
+        // a queued proposal is vetoed if a majority of the current voters now vote no
+        Status::Queued => {
+            let voters = &state.config.voters;
+            let no = proposal.votes[1]
+                .iter()
+                .filter(|a| voters.contains(a))
+                .count();
+            let abstain = proposal.votes[2]
+                .iter()
+                .filter(|a| voters.contains(a))
+                .count();
+            if has_quorum(proposal, voters) && 2 * no > voters.len().saturating_sub(abstain) {
+                proposal.status = Status::Failed;
+                proposal.executable_after = None;
+                proposal.execute_by = None;
+                state.failed.push(proposal.id);
+                state.config.queued.retain(|i| *i != proposal.id);
                 closed_prop = true;
-                save_it = true;
-                break;
             }
         }
-        if save_it {
-            let mut prop_store = PrefixedStorage::new(PREFIX_PROPOSAL, &mut deps.storage);
-            json_save(&mut prop_store, &proposal.id.to_le_bytes(), &proposal)?;
+        _ => return Ok((closed_prop, rmv_voters)),
+    }
+    if save_prop || closed_prop {
+        let mut prop_store = PrefixedStorage::new(PREFIX_PROPOSAL, &mut deps.storage);
+        json_save(&mut prop_store, &proposal.id.to_le_bytes(), &proposal)?;
+    }
+    Ok((closed_prop, rmv_voters))
+}
+
+/// Returns bool which is true if more than half of the current voters have voted on the
+/// proposal, counting abstentions
+///
+/// # Arguments
+///
+/// * `proposal` - a reference to the proposal
+/// * `voters` - the current voters
+fn has_quorum(proposal: &StoredProposal, voters: &[CanonicalAddr]) -> bool {
+    let cast = proposal
+        .votes
+        .iter()
+        .flatten()
+        .filter(|a| voters.contains(a))
+        .count();
+    2 * cast > voters.len()
+}
+
+/// Returns StdError for a proposal whose withdrawals add up to more than can be represented
+///
+/// # Arguments
+///
+/// * `id` - the proposal's ID
+fn overflow_err(id: u32) -> StdError {
+    StdError::generic_err(format!(
+        "Proposal {} withdraws more than the maximum possible amount",
+        id
+    ))
+}
+
+/// Queues a passed proposal to be executed after the timelock.  It expires if it is not
+/// executed within one voting period of becoming executable
+///
+/// # Arguments
+///
+/// * `proposal` - a mutable reference to the passed proposal
+/// * `executable_after` - time after which the proposal can be executed
+/// * `state` - a mutable reference to the State of this tx
+fn queue_proposal(proposal: &mut StoredProposal, executable_after: u64, state: &mut State) {
+    proposal.status = Status::Queued;
+    proposal.executable_after = Some(executable_after);
+    proposal.execute_by = Some(executable_after + state.config.voting_period);
+    state.queued.push(proposal.id);
+    state.config.queued.push(proposal.id);
+}
+
+/// Returns StdResult<(bool, Vec<CanonicalAddr>)> which is true if the actions were
+/// performed, and the list of voters that were removed.  If the reserve can not cover
+/// every withdrawal, none of the actions are performed
+///
+/// # Arguments
+///
+/// * `deps` - mutable reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `proposal` - a reference to the passed proposal
+/// * `state` - a mutable reference to the State of this tx
+fn execute_actions<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    proposal: &StoredProposal,
+    state: &mut State,
+) -> StdResult<(bool, Vec<CanonicalAddr>)> {
+    // total the withdrawals of each asset so the whole batch can be checked before
+    // doing anything
+    let mut totals: Vec<(Asset, Uint128)> = Vec::new();
+    for action in proposal.actions.iter() {
+        let (asset, amount) = match action {
+            StoredAction::Withdraw { amount, denom, .. } => (Asset::Native(denom.clone()), *amount),
+            StoredAction::WithdrawSnip20 { token, amount, .. } => {
+                (Asset::Snip20(token.clone()), *amount)
+            }
+            // a new stream commits every period of a bounded allowance, the first period of an
+            // open-ended one, or its whole vesting amount
+            StoredAction::CreateStream {
+                denom, schedule, ..
+            } => {
+                let amount = match schedule {
+                    Schedule::Allowance {
+                        amount, periods, ..
+                    } => amount
+                        .u128()
+                        .checked_mul(periods.unwrap_or(1) as u128)
+                        .map(Uint128)
+                        .ok_or_else(|| overflow_err(proposal.id))?,
+                    Schedule::Vesting { amount, .. } => *amount,
+                };
+                (Asset::Native(denom.clone()), amount)
+            }
+            _ => continue,
+        };
+        if let Some(total) = totals.iter_mut().find(|t| t.0 == asset) {
+            total.1 = total
+                .1
+                .u128()
+                .checked_add(amount.u128())
+                .map(Uint128)
+                .ok_or_else(|| overflow_err(proposal.id))?;
+        } else {
+            totals.push((asset, amount));
+        }
+    }
+    for (asset, total) in totals.iter() {
+        let idx = get_reserve(deps, env, asset, state)?;
+        let reserve = &state.reserves[idx];
+        let needed = reserve
+            .withdrawn
+            .u128()
+            .checked_add(total.u128())
+            .ok_or_else(|| overflow_err(proposal.id))?;
+        if needed > reserve.balance.u128() {
+            return Ok((false, Vec::new()));
         }
     }
-    Ok((closed_prop, rmv_voter))
+    let mut rmv_voters: Vec<CanonicalAddr> = Vec::new();
+    for action in proposal.actions.iter() {
+        match action {
+            StoredAction::AddVoter { address } => {
+                if !state.config.voters.contains(&address) {
+                    state.config.voters.push(address.clone());
+                }
+                // a voter rotated out and back in keeps their votes
+                rmv_voters.retain(|a| a != address);
+            }
+            StoredAction::RemoveVoter { address } => {
+                if let Some(pos) = state.config.voters.iter().position(|a| a == address) {
+                    state.config.voters.swap_remove(pos);
+                    rmv_voters.push(address.clone());
+                }
+            }
+            StoredAction::Withdraw {
+                recipient,
+                amount,
+                denom,
+            } => {
+                let idx = get_reserve(deps, env, &Asset::Native(denom.clone()), state)?;
+                state.reserves[idx].withdrawn += *amount;
+                append_ledger(
+                    &mut deps.storage,
+                    &mut state.config,
+                    StoredLedgerEntry {
+                        id: 0,
+                        tx_type: TxType::Withdraw,
+                        proposal: Some(proposal.id),
+                        stream: None,
+                        address: recipient.clone(),
+                        amount: *amount,
+                        denom: Some(denom.clone()),
+                        token: None,
+                        time: env.block.time,
+                        memo: None,
+                    },
+                )?;
+                let withdrawal_coins: Vec<Coin> = vec![Coin {
+                    denom: denom.clone(),
+                    amount: *amount,
+                }];
+                state.messages.push(CosmosMsg::Bank(BankMsg::Send {
+                    from_address: env.contract.address.clone(),
+                    to_address: deps.api.human_address(&recipient)?,
+                    amount: withdrawal_coins,
+                }));
+            }
+            StoredAction::RegisterSnip20 { token } => {
+                if !state
+                    .config
+                    .tokens
+                    .iter()
+                    .any(|t| t.address == token.address)
+                {
+                    state.config.tokens.push(token.clone());
+                    state.messages.push(set_viewing_key_msg(
+                        state.config.viewing_key.clone(),
+                        None,
+                        BLOCK_SIZE,
+                        token.code_hash.clone(),
+                        deps.api.human_address(&token.address)?,
+                    )?);
+                }
+            }
+            StoredAction::WithdrawSnip20 {
+                token,
+                recipient,
+                amount,
+            } => {
+                let idx = get_reserve(deps, env, &Asset::Snip20(token.clone()), state)?;
+                state.reserves[idx].withdrawn += *amount;
+                append_ledger(
+                    &mut deps.storage,
+                    &mut state.config,
+                    StoredLedgerEntry {
+                        id: 0,
+                        tx_type: TxType::Withdraw,
+                        proposal: Some(proposal.id),
+                        stream: None,
+                        address: recipient.clone(),
+                        amount: *amount,
+                        denom: None,
+                        token: Some(token.clone()),
+                        time: env.block.time,
+                        memo: None,
+                    },
+                )?;
+                let code_hash = state
+                    .config
+                    .tokens
+                    .iter()
+                    .find(|t| t.address == *token)
+                    .map(|t| t.code_hash.clone())
+                    .ok_or_else(|| StdError::generic_err("SNIP-20 token list is corrupt"))?;
+                state.messages.push(transfer_msg(
+                    deps.api.human_address(&recipient)?,
+                    *amount,
+                    None,
+                    BLOCK_SIZE,
+                    code_hash,
+                    deps.api.human_address(&token)?,
+                )?);
+            }
+            StoredAction::CreateStream {
+                recipient,
+                denom,
+                schedule,
+            } => {
+                let stream = StoredStream {
+                    id: state.config.stream_count,
+                    recipient: recipient.clone(),
+                    denom: denom.clone(),
+                    schedule: schedule.clone(),
+                    created: env.block.time,
+                    claimed: Uint128(0),
+                    period_start: env.block.time,
+                    period_claimed: Uint128(0),
+                };
+                let idx = get_reserve(deps, env, &Asset::Native(denom.clone()), state)?;
+                state.reserves[idx].withdrawn += stream.committed(env.block.time);
+                let mut stream_store = PrefixedStorage::new(PREFIX_STREAM, &mut deps.storage);
+                json_save(&mut stream_store, &stream.id.to_le_bytes(), &stream)?;
+                state.config.streams.push(stream.id);
+                state.config.stream_count += 1;
+            }
+            StoredAction::CancelStream { id } => {
+                state.config.streams.retain(|s| s != id);
+            }
+            StoredAction::RegisterNftContract { contract } => {
+                if !state
+                    .config
+                    .nft_contracts
+                    .iter()
+                    .any(|c| c.address == contract.address)
+                {
+                    state.config.nft_contracts.push(contract.clone());
+                    let address = deps.api.human_address(&contract.address)?;
+                    state.messages.push(register_receive_nft_msg(
+                        env.contract_code_hash.clone(),
+                        Some(true),
+                        None,
+                        BLOCK_SIZE,
+                        contract.code_hash.clone(),
+                        address.clone(),
+                    )?);
+                    state.messages.push(set_nft_viewing_key_msg(
+                        state.config.viewing_key.clone(),
+                        None,
+                        BLOCK_SIZE,
+                        contract.code_hash.clone(),
+                        address,
+                    )?);
+                }
+            }
+            StoredAction::TransferNft {
+                contract,
+                token_id,
+                recipient,
+            } => {
+                let code_hash = nft_code_hash(&state.config, contract)?;
+                state.messages.push(transfer_nft_msg(
+                    deps.api.human_address(&recipient)?,
+                    token_id.clone(),
+                    None,
+                    None,
+                    BLOCK_SIZE,
+                    code_hash,
+                    deps.api.human_address(&contract)?,
+                )?);
+            }
+            StoredAction::SendNft {
+                contract,
+                token_id,
+                recipient,
+                msg,
+            } => {
+                let code_hash = nft_code_hash(&state.config, contract)?;
+                state.messages.push(send_nft_msg(
+                    deps.api.human_address(&recipient)?,
+                    token_id.clone(),
+                    msg.clone(),
+                    None,
+                    None,
+                    BLOCK_SIZE,
+                    code_hash,
+                    deps.api.human_address(&contract)?,
+                )?);
+            }
+        }
+    }
+    // other proposals may have removed voters since this one was proposed
+    if state.config.voters.is_empty() {
+        return Err(StdError::generic_err(format!(
+            "Proposal {} can not be executed because it would remove every authorized voter",
+            proposal.id
+        )));
+    }
+    Ok((true, rmv_voters))
+}
+
+/// Returns StdResult<String> code hash of a registered SNIP-721 contract
+///
+/// # Arguments
+///
+/// * `config` - a reference to the Config
+/// * `contract` - a reference to the address of the SNIP-721 contract
+fn nft_code_hash(config: &Config, contract: &CanonicalAddr) -> StdResult<String> {
+    config
+        .nft_contracts
+        .iter()
+        .find(|c| c.address == *contract)
+        .map(|c| c.code_hash.clone())
+        .ok_or_else(|| StdError::generic_err("SNIP-721 contract list is corrupt"))
+}
+
+/// Returns StdResult<usize> index of the asset's Reserve in the State, querying the
+/// balance if this is the first time the asset is used in this tx
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `env` - a reference to the Env of contract's environment
+/// * `asset` - a reference to the Asset being withdrawn
+/// * `state` - a mutable reference to the State of this tx
+fn get_reserve<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    asset: &Asset,
+    state: &mut State,
+) -> StdResult<usize> {
+    if let Some(pos) = state.reserves.iter().position(|r| r.asset == *asset) {
+        return Ok(pos);
+    }
+    let balance = match asset {
+        Asset::Native(denom) => {
+            let balance = deps
+                .querier
+                .query_balance(&env.contract.address, denom)?
+                .amount;
+            // funds owed to active streams are not available for withdrawal
+            let stream_store = ReadonlyPrefixedStorage::new(PREFIX_STREAM, &deps.storage);
+            let mut committed: u128 = 0;
+            for id in state.config.streams.iter() {
+                let may_stream: Option<StoredStream> =
+                    json_may_load(&stream_store, &id.to_le_bytes())?;
+                if let Some(stream) = may_stream {
+                    if stream.denom == *denom {
+                        committed = committed
+                            .checked_add(stream.committed(env.block.time).u128())
+                            .ok_or_else(|| {
+                                StdError::generic_err(format!(
+                                    "Overflow totaling the {} committed to streams",
+                                    denom
+                                ))
+                            })?;
+                    }
+                }
+            }
+            Uint128(balance.u128().saturating_sub(committed))
+        }
+        Asset::Snip20(token) => {
+            let code_hash = state
+                .config
+                .tokens
+                .iter()
+                .find(|t| t.address == *token)
+                .map(|t| t.code_hash.clone())
+                .ok_or_else(|| StdError::generic_err("SNIP-20 token list is corrupt"))?;
+            balance_query(
+                &deps.querier,
+                env.contract.address.clone(),
+                state.config.viewing_key.clone(),
+                BLOCK_SIZE,
+                code_hash,
+                deps.api.human_address(token)?,
+            )?
+            .amount
+        }
+    };
+    state.reserves.push(Reserve {
+        asset: asset.clone(),
+        balance,
+        withdrawn: Uint128(0),
+    });
+    Ok(state.reserves.len() - 1)
+}
+
+fn expire_proposals<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    state: &mut State,
+) -> StdResult<()> {
+    let mut prop_store = PrefixedStorage::new(PREFIX_PROPOSAL, &mut deps.storage);
+    for prop_id in state.config.open.iter() {
+        let may_prop: Option<StoredProposal> = json_may_load(&prop_store, &prop_id.to_le_bytes())?;
+        if let Some(mut prop) = may_prop {
+            if prop.is_expired(env.block.time) {
+                prop.status = Status::Expired;
+                json_save(&mut prop_store, &prop_id.to_le_bytes(), &prop)?;
+                state.expired.push(*prop_id);
+            }
+        }
+    }
+    // queued proposals expire if they were not executed in time
+    for prop_id in state.config.queued.iter() {
+        let may_prop: Option<StoredProposal> = json_may_load(&prop_store, &prop_id.to_le_bytes())?;
+        if let Some(mut prop) = may_prop {
+            if prop.missed_execution(env.block.time) {
+                prop.status = Status::Expired;
+                prop.executable_after = None;
+                prop.execute_by = None;
+                json_save(&mut prop_store, &prop_id.to_le_bytes(), &prop)?;
+                state.expired.push(*prop_id);
+            }
+        }
+    }
+    // remove the expired proposals from the lists of open and queued proposals
+    let expired = &state.expired;
+    state.config.open.retain(|i| !expired.contains(i));
+    state.config.queued.retain(|i| !expired.contains(i));
+    Ok(())
 }
 
-fn remove_voter<S: Storage, A: Api, Q: Querier>(
+fn remove_voters<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: &Env,
-    voter: &CanonicalAddr,
+    voters: &[CanonicalAddr],
     state: &mut State,
 ) -> StdResult<()> {
     let mut open_props: Vec<StoredProposal> = Vec::new();
@@ -620,20 +2010,24 @@ fn remove_voter<S: Storage, A: Api, Q: Querier>(
             open_props.push(prop);
         }
     }
-    // remove the voter's votes from all open proposals
-    remove_votes(voter, &mut open_props);
-    // check through all open proposals to see if removing a voter made any side have
+    // remove the voters' votes from all open proposals
+    for voter in voters.iter() {
+        remove_votes(voter, &mut open_props);
+    }
+    // check through all open proposals to see if removing voters made any side have
     // enough votes to win with the new lower threshhold
     let mut idx = 0;
     while idx < open_props.len() {
-        let (it_closed, rmv_voter) = check_close(deps, env, &mut open_props[idx], false, state)?;
+        let (it_closed, rmv_voters) = check_close(deps, env, &mut open_props[idx], false, state)?;
         if it_closed {
             // remove the closed proposal from the list of open proposals
             open_props.swap_remove(idx);
-            // if another voter got removed
-            if let Some(rmv) = rmv_voter {
-                // remove the voter's votes from all open proposals
-                remove_votes(&rmv, &mut open_props);
+            // if more voters got removed
+            if !rmv_voters.is_empty() {
+                // remove the voters' votes from all open proposals
+                for rmv in rmv_voters.iter() {
+                    remove_votes(rmv, &mut open_props);
+                }
                 // now that number of voters changed again, we need to check from the
                 // beginning
                 idx = 0;
@@ -653,10 +2047,133 @@ fn remove_voter<S: Storage, A: Api, Q: Querier>(
 
 fn remove_votes(voter: &CanonicalAddr, open_props: &mut Vec<StoredProposal>) {
     for prop in open_props.iter_mut() {
-        if let Some(pos) = prop.votes[0].iter().position(|a| a == voter) {
-            prop.votes[0].swap_remove(pos);
-        } else if let Some(pos) = prop.votes[1].iter().position(|a| a == voter) {
-            prop.votes[1].swap_remove(pos);
+        remove_vote(voter, prop);
+    }
+}
+
+fn remove_vote(voter: &CanonicalAddr, proposal: &mut StoredProposal) {
+    for votes in proposal.votes.iter_mut() {
+        if let Some(pos) = votes.iter().position(|a| a == voter) {
+            votes.swap_remove(pos);
+            break;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+
+    const VOTERS: [&str; 5] = ["alice", "bob", "carol", "dave", "erin"];
+
+    // a multisig with five voters where alice has proposed adding frank
+    fn init_with_proposal(timelock: u64) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg {
+            entropy: "entropy".to_string(),
+            voting_period: Some(1000),
+            timelock: Some(timelock),
+        };
+        init(&mut deps, mock_env("alice", &[]), msg).unwrap();
+        let mut config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
+        config.voters = VOTERS
+            .iter()
+            .map(|v| deps.api.canonical_address(&HumanAddr(v.to_string())))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()
+            .unwrap();
+        save(&mut deps.storage, CONFIG_KEY, &config).unwrap();
+        let msg = HandleMsg::Propose {
+            actions: vec![Action::AddVoter {
+                address: HumanAddr("frank".to_string()),
+            }],
+            description: None,
+        };
+        handle(&mut deps, mock_env("alice", &[]), msg).unwrap();
+        deps
+    }
+
+    fn vote(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        voter: &str,
+        vote: Vote,
+    ) -> HandleResult {
+        handle(deps, mock_env(voter, &[]), HandleMsg::Vote { id: 0, vote })
+    }
+
+    fn proposal(deps: &Extern<MockStorage, MockApi, MockQuerier>) -> StoredProposal {
+        let prop_store = ReadonlyPrefixedStorage::new(PREFIX_PROPOSAL, &deps.storage);
+        json_may_load(&prop_store, &0u32.to_le_bytes())
+            .unwrap()
+            .unwrap()
+    }
+
+    fn is_voter(deps: &Extern<MockStorage, MockApi, MockQuerier>, address: &str) -> bool {
+        let config: Config = load(&deps.storage, CONFIG_KEY).unwrap();
+        let raw = deps
+            .api
+            .canonical_address(&HumanAddr(address.to_string()))
+            .unwrap();
+        config.voters.contains(&raw)
+    }
+
+    #[test]
+    fn test_pass_with_abstentions() {
+        let mut deps = init_with_proposal(0);
+        vote(&mut deps, "bob", Vote::Yes).unwrap();
+        vote(&mut deps, "carol", Vote::Abstain).unwrap();
+        // 2 of the 4 voters that did not abstain is not a majority
+        assert_eq!(proposal(&deps).status, Status::Open);
+        vote(&mut deps, "dave", Vote::Abstain).unwrap();
+        // 2 of the 3 voters that did not abstain is a majority
+        assert_eq!(proposal(&deps).status, Status::Passed);
+        assert!(is_voter(&deps, "frank"));
+    }
+
+    #[test]
+    fn test_no_quorum() {
+        let mut deps = init_with_proposal(0);
+        vote(&mut deps, "bob", Vote::Yes).unwrap();
+        // only 2 of the 5 voters have voted
+        assert_eq!(proposal(&deps).status, Status::Open);
+        let mut env = mock_env("erin", &[]);
+        env.block.time += 1000;
+        handle(&mut deps, env, HandleMsg::ExpireProposals {}).unwrap();
+        assert_eq!(proposal(&deps).status, Status::Expired);
+        assert!(!is_voter(&deps, "frank"));
+    }
+
+    #[test]
+    fn test_changed_vote() {
+        let mut deps = init_with_proposal(0);
+        vote(&mut deps, "bob", Vote::No).unwrap();
+        // voting the same way twice is an error
+        assert!(vote(&mut deps, "bob", Vote::No).is_err());
+        vote(&mut deps, "bob", Vote::Yes).unwrap();
+        let prop = proposal(&deps);
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(prop.votes[0].len(), 2);
+        assert!(prop.votes[1].is_empty());
+        vote(&mut deps, "carol", Vote::Yes).unwrap();
+        assert_eq!(proposal(&deps).status, Status::Passed);
+        assert!(is_voter(&deps, "frank"));
+    }
+
+    #[test]
+    fn test_veto_while_queued() {
+        let mut deps = init_with_proposal(100);
+        vote(&mut deps, "bob", Vote::Yes).unwrap();
+        vote(&mut deps, "carol", Vote::Yes).unwrap();
+        assert_eq!(proposal(&deps).status, Status::Queued);
+        vote(&mut deps, "bob", Vote::No).unwrap();
+        vote(&mut deps, "carol", Vote::No).unwrap();
+        // 2 of 5 is not a majority
+        assert_eq!(proposal(&deps).status, Status::Queued);
+        vote(&mut deps, "dave", Vote::No).unwrap();
+        assert_eq!(proposal(&deps).status, Status::Failed);
+        let mut env = mock_env("erin", &[]);
+        env.block.time += 100;
+        assert!(handle(&mut deps, env, HandleMsg::ExecuteProposal { id: 0 }).is_err());
+        assert!(!is_voter(&deps, "frank"));
+    }
+}
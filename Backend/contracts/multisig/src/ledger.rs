@@ -0,0 +1,96 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, StdResult, Uint128};
+
+/// types of treasury transfers
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TxType {
+    /// funds sent in with a Deposit
+    Deposit,
+    /// funds sent out by a passed proposal
+    Withdraw,
+    /// funds claimed from a stream
+    Claim,
+}
+
+/// a treasury transfer displayed in queries
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct LedgerEntry {
+    pub id: u64,
+    pub tx_type: TxType,
+    /// id of the proposal that sent the funds, if any
+    pub proposal: Option<u32>,
+    /// id of the stream the funds were claimed from, if any
+    pub stream: Option<u32>,
+    /// recipient of outgoing funds or sender of incoming funds
+    pub address: HumanAddr,
+    pub amount: Uint128,
+    /// native coin denomination.  None if this was a SNIP-20 transfer
+    pub denom: Option<String>,
+    /// SNIP-20 token contract address.  None if this was a native coin transfer
+    pub token: Option<HumanAddr>,
+    /// number of seconds since epoch time 01/01/1970 in which the transfer happened
+    pub time: u64,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredLedgerEntry {
+    pub id: u64,
+    pub tx_type: TxType,
+    pub proposal: Option<u32>,
+    pub stream: Option<u32>,
+    pub address: CanonicalAddr,
+    pub amount: Uint128,
+    pub denom: Option<String>,
+    pub token: Option<CanonicalAddr>,
+    pub time: u64,
+    pub memo: Option<String>,
+}
+
+impl StoredLedgerEntry {
+    pub fn into_humanized<A: Api>(self, api: &A) -> StdResult<LedgerEntry> {
+        let token = if let Some(tkn) = self.token {
+            Some(api.human_address(&tkn)?)
+        } else {
+            None
+        };
+        Ok(LedgerEntry {
+            id: self.id,
+            tx_type: self.tx_type,
+            proposal: self.proposal,
+            stream: self.stream,
+            address: api.human_address(&self.address)?,
+            amount: self.amount,
+            denom: self.denom,
+            token,
+            time: self.time,
+            memo: self.memo,
+        })
+    }
+}
+
+/// running totals of an asset's transfers displayed in queries
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct AssetTotal {
+    /// native coin denomination.  None if this is a SNIP-20 token
+    pub denom: Option<String>,
+    /// SNIP-20 token contract address.  None if this is a native coin
+    pub token: Option<HumanAddr>,
+    /// total amount deposited
+    pub deposited: Uint128,
+    /// total amount withdrawn and claimed
+    pub withdrawn: Uint128,
+    /// current balance held by the multisig
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredAssetTotal {
+    pub denom: Option<String>,
+    pub token: Option<CanonicalAddr>,
+    pub deposited: Uint128,
+    pub withdrawn: Uint128,
+}
@@ -1,9 +1,13 @@
 //#![allow(clippy::field_reassign_with_default)]
 pub mod contract;
+pub mod contract_info;
+pub mod ledger;
 pub mod msg;
+pub mod permit;
 pub mod proposal;
 mod rand;
 pub mod state;
+pub mod stream;
 mod utils;
 mod viewing_key;
 
@@ -1,34 +1,79 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Uint128};
+use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128};
 
+use crate::contract_info::ContractInfo;
+use crate::ledger::{AssetTotal, LedgerEntry};
+use crate::permit::Permit;
 use crate::proposal::Proposal;
+use crate::stream::Stream;
 
 /// Instantiation message
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InitMsg {
     /// entropy used for prng seed
     pub entropy: String,
+    /// optional number of seconds a proposal stays open for voting.  Defaults to 14 days
+    pub voting_period: Option<u64>,
+    /// optional number of seconds a passed proposal must wait before it can be executed.
+    /// Defaults to 0, which executes proposals as soon as they pass
+    pub timelock: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
-    /// propose an action
+    /// propose a list of actions
     Propose {
-        /// action to perform if proposal passes
-        action: Action,
+        /// actions to perform, in order, if the proposal passes.  Either all of them are
+        /// performed or none are
+        actions: Vec<Action>,
         /// optional description for the propoal
         description: Option<String>,
     },
-    /// vote on an existing proposal
+    /// vote on an existing proposal.  Voting again on an open or queued proposal changes your
+    /// vote, and a queued proposal is vetoed if a majority of voters change to no
     Vote {
         /// proposal id
         id: u32,
         /// your vote
         vote: Vote,
     },
+    /// cancel an open or queued proposal that has not expired.  Only the proposer may cancel
+    Cancel {
+        /// proposal id
+        id: u32,
+    },
+    /// execute a queued proposal whose timelock has ended.  It expires if not executed within
+    /// one voting period after that.  Anyone may call this
+    ExecuteProposal {
+        /// proposal id
+        id: u32,
+    },
+    /// NFT receiver interface
+    BatchReceiveNft {
+        /// address that sent the NFTs
+        sender: HumanAddr,
+        /// previous owner of the NFTs
+        from: HumanAddr,
+        /// list of NFTs sent from the previous owner
+        token_ids: Vec<String>,
+        /// msg specified when sending
+        msg: Option<Binary>,
+    },
+    /// send native coins to the treasury and record them in the ledger
+    Deposit {
+        /// optional note describing the deposit
+        memo: Option<String>,
+    },
+    /// claim funds from a stream you are the recipient of
+    Claim {
+        /// stream id
+        id: u32,
+        /// optional amount to claim.  Defaults to everything currently claimable
+        amount: Option<Uint128>,
+    },
     /// create a viewing key
     CreateViewingKey {
         /// entropy String used in random key generation
@@ -41,6 +86,17 @@ pub enum HandleMsg {
         /// optional message length padding
         padding: Option<String>,
     },
+    /// mark all open proposals whose voting period has ended, and all queued proposals that
+    /// were not executed within a voting period of becoming executable, as expired.  Anyone may
+    /// call this
+    ExpireProposals {},
+    /// revoke a query permit so it can no longer be used
+    RevokePermit {
+        /// name of the permit to revoke
+        permit_name: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
@@ -48,12 +104,35 @@ pub enum HandleMsg {
 pub enum HandleAnswer {
     /// response from setting and creating a viewing key
     ViewingKey { key: String },
+    /// response from revoking a permit
+    RevokePermit { permit_name: String },
     /// list of proposals that closed after either voting or creating a new one
     ClosedProposals {
         /// list of proposals that passed as a result of this tx
         passed: Vec<u32>,
         /// list of proposals that failed as a result of this tx
         failed: Vec<u32>,
+        /// list of proposals that expired as a result of this tx
+        expired: Vec<u32>,
+        /// list of proposals that passed and are waiting to be executed as a result of this tx
+        queued: Vec<u32>,
+    },
+    /// response from cancelling a proposal
+    Cancel {
+        /// id of the cancelled proposal
+        id: u32,
+    },
+    /// response from depositing funds
+    Deposit {
+        /// coins deposited
+        amount: Vec<Coin>,
+    },
+    /// response from claiming from a stream
+    Claim {
+        /// amount claimed
+        amount: Uint128,
+        /// denomination claimed
+        denom: String,
     },
 }
 
@@ -72,12 +151,15 @@ pub enum QueryMsg {
         /// number of proposals to display
         limit: Option<u32>,
     },
-    /// list only the open proposals
+    /// list only the open proposals.  Proposals that have been marked as expired are excluded
     ListOpenProposals {
         /// address of the querier
         address: HumanAddr,
         /// querier's viewing key
         viewing_key: String,
+        /// optional current time in seconds since epoch time 01/01/1970, used to also
+        /// exclude proposals that have expired but have not been marked yet
+        time: Option<u64>,
     },
     /// display a single proposal
     Proposal {
@@ -95,6 +177,56 @@ pub enum QueryMsg {
         /// querier's viewing key
         viewing_key: String,
     },
+    /// display the multisig's native coin and registered SNIP-20 token balances
+    Balances {
+        /// address of the querier
+        address: HumanAddr,
+        /// querier's viewing key
+        viewing_key: String,
+    },
+    /// list the treasury ledger in descending order
+    Ledger {
+        /// address of the querier
+        address: HumanAddr,
+        /// querier's viewing key
+        viewing_key: String,
+        /// optional ledger entry id to start listing from.  If not specified, it will
+        /// start with the most recent entry
+        start_at: Option<u64>,
+        /// number of entries to display
+        limit: Option<u32>,
+    },
+    /// display the running totals and current balance of every asset the treasury has moved
+    Treasury {
+        /// address of the querier
+        address: HumanAddr,
+        /// querier's viewing key
+        viewing_key: String,
+    },
+    /// list the NFTs held by the multisig on each registered SNIP-721 contract
+    HeldNfts {
+        /// address of the querier
+        address: HumanAddr,
+        /// querier's viewing key
+        viewing_key: String,
+        /// optional SNIP-721 contract to limit the list to
+        contract: Option<HumanAddr>,
+        /// optional token id to start listing after.  Use this for pagination.  Requires a
+        /// contract to be specified
+        start_after: Option<String>,
+        /// optional number of tokens to display per contract
+        limit: Option<u32>,
+    },
+    /// display active streams.  Voters see every stream, and recipients see their own
+    Streams {
+        /// address of the querier
+        address: HumanAddr,
+        /// querier's viewing key
+        viewing_key: String,
+        /// optional current time in seconds since epoch time 01/01/1970, used to display
+        /// claimable amounts
+        time: Option<u64>,
+    },
     /// display a voter's vote on a specific proposal
     Vote {
         /// proposal id
@@ -106,6 +238,72 @@ pub enum QueryMsg {
         /// viewing key of the querier
         viewing_key: String,
     },
+    /// perform a query authenticated by a permit instead of a viewing key
+    WithPermit {
+        /// permit signed by the querier
+        permit: Permit,
+        /// the query to perform
+        query: QueryWithPermit,
+    },
+}
+
+/// queries that can be authenticated by a permit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryWithPermit {
+    /// list all proposals in descending order.  Requires the proposals permission
+    ListAllProposals {
+        /// optional proposal id to start listing from
+        start_at: Option<u32>,
+        /// number of proposals to display
+        limit: Option<u32>,
+    },
+    /// list only the open proposals.  Requires the proposals permission
+    ListOpenProposals {
+        /// optional current time in seconds since epoch time 01/01/1970, used to also
+        /// exclude proposals that have expired but have not been marked yet
+        time: Option<u64>,
+    },
+    /// display a single proposal.  Requires the proposals permission
+    Proposal {
+        /// proposal id
+        id: u32,
+    },
+    /// display the list of authorized voters.  Requires the proposals permission
+    ListVoters {},
+    /// display a voter's vote on a specific proposal.  Requires the proposals permission
+    Vote {
+        /// proposal id
+        id: u32,
+        /// voter whose vote history is requested
+        voter: HumanAddr,
+    },
+    /// display the treasury balances.  Requires the treasury permission
+    Balances {},
+    /// list the treasury ledger in descending order.  Requires the treasury permission
+    Ledger {
+        /// optional ledger entry id to start listing from
+        start_at: Option<u64>,
+        /// number of entries to display
+        limit: Option<u32>,
+    },
+    /// display the treasury summary.  Requires the treasury permission
+    Treasury {},
+    /// list the NFTs held by the multisig.  Requires the treasury permission
+    HeldNfts {
+        /// optional SNIP-721 contract to limit the list to
+        contract: Option<HumanAddr>,
+        /// optional token id to start listing after.  Use this for pagination.  Requires a
+        /// contract to be specified
+        start_after: Option<String>,
+        /// optional number of tokens to display per contract
+        limit: Option<u32>,
+    },
+    /// display active streams.  Requires the treasury permission
+    Streams {
+        /// optional current time in seconds since epoch time 01/01/1970
+        time: Option<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
@@ -117,11 +315,48 @@ pub enum QueryAnswer {
     ProposalInfo { proposal: Proposal },
     /// list of voters
     VoterList { voters: Vec<HumanAddr> },
+    /// list of streams
+    StreamList { streams: Vec<Stream> },
+    /// list of ledger entries
+    Ledger {
+        entries: Vec<LedgerEntry>,
+        /// total number of ledger entries
+        count: u64,
+    },
+    /// treasury summary
+    Treasury { totals: Vec<AssetTotal> },
+    /// NFTs held by the multisig
+    HeldNfts { nfts: Vec<HeldNfts> },
     /// history of a vote
     Vote {
         /// a voter's history for a specific proposal.  None if they did not vote
         vote: Option<Vote>,
     },
+    /// treasury balances
+    Balances {
+        /// native coin balances
+        native: Vec<Coin>,
+        /// balances of the registered SNIP-20 tokens
+        snip20: Vec<Snip20Balance>,
+    },
+}
+
+/// NFTs of a SNIP-721 contract held by the multisig
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct HeldNfts {
+    /// address of the SNIP-721 contract
+    pub contract: HumanAddr,
+    /// token ids held
+    pub token_ids: Vec<String>,
+}
+
+/// balance of a SNIP-20 token held by the multisig
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct Snip20Balance {
+    /// address of the token contract
+    pub token: HumanAddr,
+    /// amount held
+    pub amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
@@ -129,6 +364,8 @@ pub enum QueryAnswer {
 pub enum Vote {
     Yes,
     No,
+    /// counts toward quorum, but not toward either side winning
+    Abstain,
 }
 
 /// status of a proposal
@@ -136,8 +373,14 @@ pub enum Vote {
 #[serde(rename_all = "snake_case")]
 pub enum Status {
     Open,
+    /// passed, but waiting to be executed
+    Queued,
     Passed,
     Failed,
+    /// the voting period ended without a decision, or a queued proposal was not executed
+    /// in time
+    Expired,
+    Cancelled,
 }
 
 /// actions that a proposal can take
@@ -148,11 +391,86 @@ pub enum Action {
     AddVoter { address: HumanAddr },
     /// remove a voter from the authorized list
     RemoveVoter { address: HumanAddr },
-    /// withdraw funds from the contract
+    /// withdraw native coins from the contract
     Withdraw {
         /// address to send the funds to
         recipient: HumanAddr,
         /// amount to send
         amount: Uint128,
+        /// optional denomination to send.  Defaults to uscrt
+        denom: Option<String>,
+    },
+    /// register a SNIP-20 token contract so its tokens can be withdrawn
+    RegisterSnip20 {
+        /// the token's ContractInfo
+        token: ContractInfo,
+    },
+    /// withdraw tokens of a registered SNIP-20 contract
+    WithdrawSnip20 {
+        /// address of the token contract
+        token: HumanAddr,
+        /// address to send the tokens to
+        recipient: HumanAddr,
+        /// amount to send
+        amount: Uint128,
+    },
+    /// create a stream the recipient can claim native coins from
+    CreateStream {
+        /// address allowed to claim
+        recipient: HumanAddr,
+        /// optional denomination to pay.  Defaults to uscrt
+        denom: Option<String>,
+        /// how the claimable amount grows
+        schedule: Schedule,
+    },
+    /// stop a stream so nothing more can be claimed
+    CancelStream {
+        /// stream id
+        id: u32,
+    },
+    /// register a SNIP-721 contract so the multisig can receive and hold its NFTs
+    RegisterNftContract { contract: ContractInfo },
+    /// transfer an NFT held by the multisig
+    TransferNft {
+        /// address of the SNIP-721 contract
+        contract: HumanAddr,
+        /// id of the token to transfer
+        token_id: String,
+        /// address to transfer the token to
+        recipient: HumanAddr,
+    },
+    /// send an NFT held by the multisig to a contract, calling its receiver interface
+    SendNft {
+        /// address of the SNIP-721 contract
+        contract: HumanAddr,
+        /// id of the token to send
+        token_id: String,
+        /// address of the contract to send the token to
+        recipient: HumanAddr,
+        /// optional msg to pass to the recipient contract
+        msg: Option<Binary>,
+    },
+}
+
+/// how the claimable amount of a stream grows
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// the recipient may claim up to `amount` every `period` seconds.  Unclaimed amounts do
+    /// not carry over to the next period.  If `periods` is given, the allowance ends after
+    /// that many periods and the treasury reserves every remaining period.  Otherwise it is
+    /// open-ended and only the current period is reserved, so later periods are paid only if
+    /// the treasury still has the funds
+    Allowance {
+        amount: Uint128,
+        period: u64,
+        periods: Option<u32>,
+    },
+    /// `amount` vests linearly between the `start` and `end` times, in seconds since epoch
+    /// time 01/01/1970
+    Vesting {
+        amount: Uint128,
+        start: u64,
+        end: u64,
     },
 }
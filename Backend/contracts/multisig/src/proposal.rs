@@ -1,18 +1,32 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, StdResult, Uint128};
+use cosmwasm_std::{Api, Binary, CanonicalAddr, HumanAddr, StdResult, Uint128};
 
-use crate::msg::{Action, Status};
+use crate::contract_info::StoreContractInfo;
+use crate::msg::{Action, Schedule, Status};
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
 pub struct Proposal {
     pub id: u32,
-    pub action: Action,
+    pub proposer: HumanAddr,
+    pub actions: Vec<Action>,
     pub status: Status,
     pub description: Option<String>,
+    /// number of seconds since epoch time 01/01/1970 in which the proposal was created
+    pub created: u64,
+    /// number of seconds since epoch time 01/01/1970 after which the proposal can no
+    /// longer be voted on
+    pub expires: u64,
+    /// number of seconds since epoch time 01/01/1970 after which a queued proposal can be
+    /// executed
+    pub executable_after: Option<u64>,
+    /// number of seconds since epoch time 01/01/1970 after which a queued proposal can no
+    /// longer be executed
+    pub execute_by: Option<u64>,
     pub yes: Vec<HumanAddr>,
     pub no: Vec<HumanAddr>,
+    pub abstain: Vec<HumanAddr>,
 }
 
 /// actions that a proposal can take
@@ -23,12 +37,48 @@ pub enum StoredAction {
     AddVoter { address: CanonicalAddr },
     /// remove a voter from the authorized list
     RemoveVoter { address: CanonicalAddr },
-    /// withdraw funds from the contract
+    /// withdraw native coins from the contract
     Withdraw {
         /// address to send the funds to
         recipient: CanonicalAddr,
         /// amount to send
         amount: Uint128,
+        /// denomination to send
+        denom: String,
+    },
+    /// register a SNIP-20 token contract so its tokens can be withdrawn
+    RegisterSnip20 { token: StoreContractInfo },
+    /// withdraw tokens of a registered SNIP-20 contract
+    WithdrawSnip20 {
+        /// address of the token contract
+        token: CanonicalAddr,
+        /// address to send the tokens to
+        recipient: CanonicalAddr,
+        /// amount to send
+        amount: Uint128,
+    },
+    /// create a stream the recipient can claim native coins from
+    CreateStream {
+        recipient: CanonicalAddr,
+        denom: String,
+        schedule: Schedule,
+    },
+    /// stop a stream so nothing more can be claimed
+    CancelStream { id: u32 },
+    /// register a SNIP-721 contract so the multisig can receive and hold its NFTs
+    RegisterNftContract { contract: StoreContractInfo },
+    /// transfer an NFT held by the multisig
+    TransferNft {
+        contract: CanonicalAddr,
+        token_id: String,
+        recipient: CanonicalAddr,
+    },
+    /// send an NFT held by the multisig to a contract
+    SendNft {
+        contract: CanonicalAddr,
+        token_id: String,
+        recipient: CanonicalAddr,
+        msg: Option<Binary>,
     },
 }
 
@@ -41,10 +91,60 @@ impl StoredAction {
             StoredAction::RemoveVoter { address } => Action::RemoveVoter {
                 address: api.human_address(&address)?,
             },
-            StoredAction::Withdraw { recipient, amount } => Action::Withdraw {
+            StoredAction::Withdraw {
+                recipient,
+                amount,
+                denom,
+            } => Action::Withdraw {
+                recipient: api.human_address(&recipient)?,
+                amount,
+                denom: Some(denom),
+            },
+            StoredAction::RegisterSnip20 { token } => Action::RegisterSnip20 {
+                token: token.into_humanized(api)?,
+            },
+            StoredAction::WithdrawSnip20 {
+                token,
+                recipient,
+                amount,
+            } => Action::WithdrawSnip20 {
+                token: api.human_address(&token)?,
                 recipient: api.human_address(&recipient)?,
                 amount,
             },
+            StoredAction::CreateStream {
+                recipient,
+                denom,
+                schedule,
+            } => Action::CreateStream {
+                recipient: api.human_address(&recipient)?,
+                denom: Some(denom),
+                schedule,
+            },
+            StoredAction::CancelStream { id } => Action::CancelStream { id },
+            StoredAction::RegisterNftContract { contract } => Action::RegisterNftContract {
+                contract: contract.into_humanized(api)?,
+            },
+            StoredAction::TransferNft {
+                contract,
+                token_id,
+                recipient,
+            } => Action::TransferNft {
+                contract: api.human_address(&contract)?,
+                token_id,
+                recipient: api.human_address(&recipient)?,
+            },
+            StoredAction::SendNft {
+                contract,
+                token_id,
+                recipient,
+                msg,
+            } => Action::SendNft {
+                contract: api.human_address(&contract)?,
+                token_id,
+                recipient: api.human_address(&recipient)?,
+                msg,
+            },
         };
         Ok(action)
     }
@@ -53,19 +153,53 @@ impl StoredAction {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct StoredProposal {
     pub id: u32,
-    pub action: StoredAction,
+    pub proposer: CanonicalAddr,
+    pub actions: Vec<StoredAction>,
     pub status: Status,
     pub description: Option<String>,
-    pub votes: [Vec<CanonicalAddr>; 2],
+    pub created: u64,
+    pub expires: u64,
+    pub executable_after: Option<u64>,
+    #[serde(default)]
+    pub execute_by: Option<u64>,
+    /// yes, no, and abstain votes
+    pub votes: [Vec<CanonicalAddr>; 3],
 }
 
 impl StoredProposal {
+    /// Returns bool that is true if the proposal's voting period has ended
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - current block time in seconds since epoch time 01/01/1970
+    pub fn is_expired(&self, time: u64) -> bool {
+        time >= self.expires
+    }
+
+    /// Returns bool that is true if the proposal was queued but not executed in time
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - current block time in seconds since epoch time 01/01/1970
+    pub fn missed_execution(&self, time: u64) -> bool {
+        self.execute_by.map_or(false, |t| time >= t)
+    }
+
     pub fn into_humanized<A: Api>(self, api: &A) -> StdResult<Proposal> {
         Ok(Proposal {
             id: self.id,
-            action: self.action.into_humanized(api)?,
+            proposer: api.human_address(&self.proposer)?,
+            actions: self
+                .actions
+                .into_iter()
+                .map(|a| a.into_humanized(api))
+                .collect::<StdResult<Vec<Action>>>()?,
             status: self.status,
             description: self.description,
+            created: self.created,
+            expires: self.expires,
+            executable_after: self.executable_after,
+            execute_by: self.execute_by,
             yes: self.votes[0]
                 .iter()
                 .map(|a| api.human_address(&a))
@@ -74,6 +208,10 @@ impl StoredProposal {
                 .iter()
                 .map(|a| api.human_address(&a))
                 .collect::<StdResult<Vec<HumanAddr>>>()?,
+            abstain: self.votes[2]
+                .iter()
+                .map(|a| api.human_address(&a))
+                .collect::<StdResult<Vec<HumanAddr>>>()?,
         })
     }
 }
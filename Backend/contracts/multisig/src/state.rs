@@ -3,11 +3,13 @@ use std::any::type_name;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use cosmwasm_std::{
-    Api, CanonicalAddr, Extern, Querier, ReadonlyStorage, StdError, StdResult, Storage,
+    Api, CanonicalAddr, Extern, Querier, ReadonlyStorage, StdError, StdResult, Storage, Uint128,
 };
 
-use cosmwasm_storage::ReadonlyPrefixedStorage;
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 
+use crate::contract_info::StoreContractInfo;
+use crate::ledger::{LedgerEntry, StoredAssetTotal, StoredLedgerEntry, TxType};
 use crate::proposal::{Proposal, StoredProposal};
 
 use secret_toolkit::serialization::{Bincode2, Json, Serde};
@@ -20,6 +22,14 @@ pub const PRNG_KEY: &[u8] = b"prngseed";
 pub const PREFIX_VIEW_KEY: &[u8] = b"viewkey";
 /// prefix for proposal storage
 pub const PREFIX_PROPOSAL: &[u8] = b"proposal";
+/// prefix for stream storage
+pub const PREFIX_STREAM: &[u8] = b"stream";
+/// prefix for ledger storage
+pub const PREFIX_LEDGER: &[u8] = b"ledger";
+/// key to store the running totals of each asset's transfers
+pub const TOTALS_KEY: &[u8] = b"totals";
+/// default number of seconds a proposal stays open (14 days)
+pub const DEFAULT_VOTING_PERIOD: u64 = 1_209_600;
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
@@ -29,6 +39,93 @@ pub struct Config {
     pub count: u32,
     /// list of open proposals
     pub open: Vec<u32>,
+    /// list of queued proposals
+    pub queued: Vec<u32>,
+    /// number of seconds a proposal stays open for voting
+    pub voting_period: u64,
+    /// number of seconds a passed proposal waits before it can be executed
+    pub timelock: u64,
+    /// this contract's address
+    pub my_address: CanonicalAddr,
+    /// registered SNIP-20 token contracts
+    pub tokens: Vec<StoreContractInfo>,
+    /// viewing key used with the registered SNIP-20 token contracts
+    pub viewing_key: String,
+    /// number of streams
+    pub stream_count: u32,
+    /// list of active streams
+    pub streams: Vec<u32>,
+    /// number of ledger entries
+    pub ledger_count: u64,
+    /// registered SNIP-721 contracts
+    pub nft_contracts: Vec<StoreContractInfo>,
+}
+
+/// Returns StdResult<()> after appending a transfer to the ledger and updating the
+/// asset's running totals
+///
+/// # Arguments
+///
+/// * `storage` - a mutable reference to the contract's storage
+/// * `config` - a mutable reference to the Config
+/// * `entry` - the transfer to record.  Its id will be assigned here
+pub fn append_ledger<S: Storage>(
+    storage: &mut S,
+    config: &mut Config,
+    mut entry: StoredLedgerEntry,
+) -> StdResult<()> {
+    let mut totals: Vec<StoredAssetTotal> = json_may_load(storage, TOTALS_KEY)?.unwrap_or_default();
+    let pos = if let Some(pos) = totals
+        .iter()
+        .position(|t| t.denom == entry.denom && t.token == entry.token)
+    {
+        pos
+    } else {
+        totals.push(StoredAssetTotal {
+            denom: entry.denom.clone(),
+            token: entry.token.clone(),
+            deposited: Uint128(0),
+            withdrawn: Uint128(0),
+        });
+        totals.len() - 1
+    };
+    if entry.tx_type == TxType::Deposit {
+        totals[pos].deposited += entry.amount;
+    } else {
+        totals[pos].withdrawn += entry.amount;
+    }
+    json_save(storage, TOTALS_KEY, &totals)?;
+    entry.id = config.ledger_count;
+    let mut ledger_store = PrefixedStorage::new(PREFIX_LEDGER, storage);
+    json_save(&mut ledger_store, &entry.id.to_le_bytes(), &entry)?;
+    config.ledger_count += 1;
+    Ok(())
+}
+
+/// Returns StdResult<Vec<LedgerEntry>> of the ledger entries to display
+///
+/// # Arguments
+///
+/// * `deps` - a reference to Extern containing all the contract's external dependencies
+/// * `start_at` - first ledger entry to display
+/// * `limit` - number of ledger entries to display
+pub fn get_ledger<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_at: u64,
+    limit: u32,
+) -> StdResult<Vec<LedgerEntry>> {
+    let ledger_store = ReadonlyPrefixedStorage::new(PREFIX_LEDGER, &deps.storage);
+    let end_range = start_at + 1;
+    let start_range = end_range.saturating_sub(limit as u64);
+    let mut entries: Vec<LedgerEntry> = Vec::new();
+    for idx in (start_range..end_range).rev() {
+        let may_entry: Option<StoredLedgerEntry> =
+            json_may_load(&ledger_store, &idx.to_le_bytes())?;
+        if let Some(entry) = may_entry {
+            entries.push(entry.into_humanized(&deps.api)?);
+        }
+    }
+    Ok(entries)
 }
 
 /// Returns StdResult<Vec<Proposal>> of the proposals to display
@@ -0,0 +1,177 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, StdResult, Uint128};
+
+use crate::msg::Schedule;
+
+/// a recurring payout displayed in queries
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct Stream {
+    pub id: u32,
+    pub recipient: HumanAddr,
+    pub denom: String,
+    pub schedule: Schedule,
+    /// number of seconds since epoch time 01/01/1970 in which the stream was created
+    pub created: u64,
+    /// total amount claimed so far
+    pub claimed: Uint128,
+    /// amount that can be claimed at the time given in the query, if one was given
+    pub claimable: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredStream {
+    pub id: u32,
+    pub recipient: CanonicalAddr,
+    pub denom: String,
+    pub schedule: Schedule,
+    pub created: u64,
+    pub claimed: Uint128,
+    /// start of the allowance period of the last claim
+    pub period_start: u64,
+    /// amount claimed during the allowance period of the last claim
+    pub period_claimed: Uint128,
+}
+
+impl StoredStream {
+    /// Returns u64 start time of the allowance period that contains the input time
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - seconds since epoch time 01/01/1970
+    fn period_start_at(&self, time: u64) -> u64 {
+        match &self.schedule {
+            Schedule::Allowance { period, .. } => {
+                self.created + (time.saturating_sub(self.created) / period) * period
+            }
+            Schedule::Vesting { .. } => self.created,
+        }
+    }
+
+    /// Returns Option<u64> number of allowance periods left after the one that contains the
+    /// input time, or None if the allowance is open-ended
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - seconds since epoch time 01/01/1970
+    fn periods_left(&self, time: u64) -> Option<u64> {
+        match &self.schedule {
+            Schedule::Allowance {
+                period, periods, ..
+            } => periods.map(|n| {
+                let current = time.saturating_sub(self.created) / period;
+                (n as u64).saturating_sub(current + 1)
+            }),
+            Schedule::Vesting { .. } => Some(0),
+        }
+    }
+
+    /// Returns bool that is true if the input time is after the last period of a bounded
+    /// allowance
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - seconds since epoch time 01/01/1970
+    fn allowance_ended(&self, time: u64) -> bool {
+        match &self.schedule {
+            Schedule::Allowance {
+                period, periods, ..
+            } => periods.map_or(false, |n| {
+                time.saturating_sub(self.created) / period >= n as u64
+            }),
+            Schedule::Vesting { .. } => false,
+        }
+    }
+
+    /// Returns Uint128 amount the recipient can claim at the input time
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - seconds since epoch time 01/01/1970
+    pub fn claimable(&self, time: u64) -> Uint128 {
+        match &self.schedule {
+            Schedule::Allowance { amount, .. } => {
+                if self.allowance_ended(time) {
+                    Uint128(0)
+                // unclaimed amounts do not carry over to the next period
+                } else if self.period_start_at(time) == self.period_start {
+                    Uint128(amount.u128().saturating_sub(self.period_claimed.u128()))
+                } else {
+                    *amount
+                }
+            }
+            Schedule::Vesting { amount, start, end } => {
+                let vested = if time <= *start {
+                    0
+                } else if time >= *end {
+                    amount.u128()
+                } else {
+                    amount.u128() * (time - start) as u128 / (end - start) as u128
+                };
+                Uint128(vested.saturating_sub(self.claimed.u128()))
+            }
+        }
+    }
+
+    /// Returns Uint128 amount the treasury must keep in reserve for this stream.  An
+    /// open-ended allowance only reserves its current period
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - seconds since epoch time 01/01/1970
+    pub fn committed(&self, time: u64) -> Uint128 {
+        match &self.schedule {
+            Schedule::Allowance { amount, .. } => {
+                let future = self.periods_left(time).unwrap_or(0) as u128;
+                Uint128(self.claimable(time).u128() + amount.u128() * future)
+            }
+            Schedule::Vesting { amount, .. } => {
+                Uint128(amount.u128().saturating_sub(self.claimed.u128()))
+            }
+        }
+    }
+
+    /// records a claim
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount claimed
+    /// * `time` - seconds since epoch time 01/01/1970
+    pub fn claim(&mut self, amount: Uint128, time: u64) {
+        let period_start = self.period_start_at(time);
+        if period_start != self.period_start {
+            self.period_start = period_start;
+            self.period_claimed = Uint128(0);
+        }
+        self.period_claimed += amount;
+        self.claimed += amount;
+    }
+
+    /// Returns bool that is true if nothing more can ever be claimed from this stream
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - seconds since epoch time 01/01/1970
+    pub fn is_finished(&self, time: u64) -> bool {
+        match &self.schedule {
+            Schedule::Allowance { .. } => {
+                self.periods_left(time) == Some(0) && self.claimable(time).u128() == 0
+            }
+            Schedule::Vesting { amount, .. } => self.claimed >= *amount,
+        }
+    }
+
+    pub fn into_humanized<A: Api>(self, api: &A, time: Option<u64>) -> StdResult<Stream> {
+        let claimable = time.map(|t| self.claimable(t));
+        Ok(Stream {
+            id: self.id,
+            recipient: api.human_address(&self.recipient)?,
+            denom: self.denom,
+            schedule: self.schedule,
+            created: self.created,
+            claimed: self.claimed,
+            claimable,
+        })
+    }
+}